@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use jsonwebtoken::{crypto, Algorithm, DecodingKey};
+use rusteze_models::{FederationEnvelope, FederationEvent, FEDERATION_VERSION};
+
+use crate::{token::KeyMaterial, AuthError, AuthResult};
+
+/// Verifying keys of trusted peer deployments, keyed by origin name.
+pub type PeerKeys = HashMap<String, DecodingKey>;
+
+/// Sign an event for delivery to other deployments. Requires RS256 keys with a private key.
+pub fn sign(
+    origin: &str,
+    event: FederationEvent,
+    keys: &KeyMaterial,
+) -> AuthResult<FederationEnvelope> {
+    let KeyMaterial::Rs256 {
+        private_key: Some(key),
+        ..
+    } = keys
+    else {
+        return Err(AuthError::SigningKeyUnavailable);
+    };
+
+    let origin_ts = Utc::now().timestamp();
+    let payload = FederationEnvelope::signing_payload(FEDERATION_VERSION, origin, origin_ts, &event)
+        .map_err(|_| AuthError::InvalidSignature)?;
+    let signature =
+        crypto::sign(&payload, key, Algorithm::RS256).map_err(|_| AuthError::InvalidSignature)?;
+
+    Ok(FederationEnvelope {
+        version: FEDERATION_VERSION,
+        origin: origin.to_string(),
+        origin_ts,
+        event,
+        signature,
+    })
+}
+
+/// Verify an envelope against the origin's public key. This only checks the signature;
+/// freshness and replay checks on `origin_ts` are up to the receiver.
+pub fn verify(envelope: &FederationEnvelope, public_key: &DecodingKey) -> AuthResult<()> {
+    let payload = FederationEnvelope::signing_payload(
+        envelope.version,
+        &envelope.origin,
+        envelope.origin_ts,
+        &envelope.event,
+    )
+    .map_err(|_| AuthError::InvalidSignature)?;
+
+    match crypto::verify(&envelope.signature, &payload, public_key, Algorithm::RS256) {
+        Ok(true) => Ok(()),
+        _ => Err(AuthError::InvalidSignature),
+    }
+}
+
+/// Parse trusted peers from a `name=/path/to/public.pem,other=/path/other.pem` list.
+pub fn load_peer_keys(spec: &str) -> AuthResult<PeerKeys> {
+    let mut peers = PeerKeys::new();
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (origin, path) = entry
+            .split_once('=')
+            .ok_or_else(|| AuthError::InvalidKey(format!("malformed peer entry: {entry}")))?;
+        let pem = std::fs::read(path).map_err(|e| AuthError::InvalidKey(format!("{path}: {e}")))?;
+        let key = DecodingKey::from_rsa_pem(&pem)
+            .map_err(|e| AuthError::InvalidKey(format!("{origin}: {e}")))?;
+        peers.insert(origin.to_string(), key);
+    }
+
+    Ok(peers)
+}
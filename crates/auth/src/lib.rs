@@ -1,3 +1,4 @@
+pub mod federation;
 pub mod password;
 pub mod session;
 pub mod token;
@@ -18,6 +19,12 @@ pub enum AuthError {
     MfaRequired,
     #[error("invalid mfa code")]
     InvalidMfaCode,
+    #[error("signing key unavailable")]
+    SigningKeyUnavailable,
+    #[error("invalid key material: {0}")]
+    InvalidKey(String),
+    #[error("invalid signature")]
+    InvalidSignature,
     #[error("database error: {0}")]
     Db(#[from] rusteze_db::DbError),
 }
@@ -1,7 +1,7 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::{password, token, AuthResult};
+use crate::{password, token::{self, KeyMaterial}, AuthResult};
 
 pub struct LoginResult {
     pub user_id: Uuid,
@@ -9,19 +9,21 @@ pub struct LoginResult {
     pub token: String,
 }
 
-/// Register a new user.
+/// Register a new user. `unique_username` selects discriminator-free usernames.
 pub async fn register(
     pool: &PgPool,
     username: &str,
     email: &str,
     password: &str,
-    jwt_secret: &str,
+    unique_username: bool,
+    keys: &KeyMaterial,
 ) -> AuthResult<LoginResult> {
     let hash = password::hash_password(password)?;
-    let user = rusteze_db::users::create_user(pool, username, email, &hash).await?;
+    let user =
+        rusteze_db::users::create_user(pool, username, email, &hash, unique_username).await?;
     let session_id = Uuid::now_v7();
 
-    let token_str = token::create_token(user.id, session_id, jwt_secret)?;
+    let token_str = token::create_token(user.id, session_id, keys)?;
     let token_hash = sha256_hex(&token_str);
 
     sqlx::query("INSERT INTO sessions (id, user_id, token_hash) VALUES ($1, $2, $3)")
@@ -44,7 +46,7 @@ pub async fn login(
     pool: &PgPool,
     email: &str,
     password_raw: &str,
-    jwt_secret: &str,
+    keys: &KeyMaterial,
 ) -> AuthResult<LoginResult> {
     let user = rusteze_db::users::find_by_email(pool, email)
         .await
@@ -53,7 +55,7 @@ pub async fn login(
     password::verify_password(password_raw, &user.password_hash)?;
 
     let session_id = Uuid::now_v7();
-    let token_str = token::create_token(user.id, session_id, jwt_secret)?;
+    let token_str = token::create_token(user.id, session_id, keys)?;
     let token_hash = sha256_hex(&token_str);
 
     sqlx::query("INSERT INTO sessions (id, user_id, token_hash) VALUES ($1, $2, $3)")
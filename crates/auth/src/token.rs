@@ -1,9 +1,9 @@
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::AuthResult;
+use crate::{AuthError, AuthResult};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -13,8 +13,73 @@ pub struct Claims {
     pub iat: i64,        // issued at
 }
 
+/// Key material for signing and verifying session tokens.
+pub enum KeyMaterial {
+    /// HS256 with a shared secret. Anyone who can verify can also sign.
+    Hs256 { secret: String },
+    /// RS256. Verifiers like the gateway only need the public key, so `private_key`
+    /// is `None` in processes that never mint tokens.
+    Rs256 {
+        private_key: Option<EncodingKey>,
+        public_key: DecodingKey,
+    },
+}
+
+impl KeyMaterial {
+    /// Build RS256 key material from PEM-encoded RSA keys.
+    pub fn rs256_from_pem(private_pem: Option<&[u8]>, public_pem: &[u8]) -> AuthResult<Self> {
+        let private_key = private_pem
+            .map(EncodingKey::from_rsa_pem)
+            .transpose()
+            .map_err(|e| AuthError::InvalidKey(format!("private key: {e}")))?;
+        let public_key = DecodingKey::from_rsa_pem(public_pem)
+            .map_err(|e| AuthError::InvalidKey(format!("public key: {e}")))?;
+
+        Ok(KeyMaterial::Rs256 {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Load key material from the environment.
+    ///
+    /// - `JWT_ALGORITHM`: `HS256` (default) or `RS256`
+    /// - `JWT_SECRET`: shared secret for HS256
+    /// - `JWT_PRIVATE_KEY_PATH`: RSA private key PEM for RS256 (omit on verify-only services)
+    /// - `JWT_PUBLIC_KEY_PATH`: RSA public key PEM for RS256
+    pub fn from_env() -> AuthResult<Self> {
+        let algorithm = std::env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".into());
+
+        match algorithm.to_ascii_uppercase().as_str() {
+            "HS256" => Ok(KeyMaterial::Hs256 {
+                secret: std::env::var("JWT_SECRET")
+                    .unwrap_or_else(|_| "dev-secret-change-me".into()),
+            }),
+            "RS256" => {
+                let private_pem = std::env::var("JWT_PRIVATE_KEY_PATH")
+                    .ok()
+                    .map(|path| read_pem(&path))
+                    .transpose()?;
+                let public_path = std::env::var("JWT_PUBLIC_KEY_PATH").map_err(|_| {
+                    AuthError::InvalidKey("JWT_PUBLIC_KEY_PATH must be set for RS256".into())
+                })?;
+                let public_pem = read_pem(&public_path)?;
+
+                Self::rs256_from_pem(private_pem.as_deref(), &public_pem)
+            }
+            other => Err(AuthError::InvalidKey(format!(
+                "unsupported JWT_ALGORITHM: {other}"
+            ))),
+        }
+    }
+}
+
+fn read_pem(path: &str) -> AuthResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| AuthError::InvalidKey(format!("{path}: {e}")))
+}
+
 /// Create a JWT for a user session.
-pub fn create_token(user_id: Uuid, session_id: Uuid, secret: &str) -> AuthResult<String> {
+pub fn create_token(user_id: Uuid, session_id: Uuid, keys: &KeyMaterial) -> AuthResult<String> {
     let now = Utc::now();
     let claims = Claims {
         sub: user_id,
@@ -23,24 +88,41 @@ pub fn create_token(user_id: Uuid, session_id: Uuid, secret: &str) -> AuthResult
         iat: now.timestamp(),
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|_| crate::AuthError::InvalidToken)
+    let token = match keys {
+        KeyMaterial::Hs256 { secret } => encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        ),
+        KeyMaterial::Rs256 {
+            private_key: Some(key),
+            ..
+        } => encode(&Header::new(Algorithm::RS256), &claims, key),
+        KeyMaterial::Rs256 {
+            private_key: None, ..
+        } => return Err(AuthError::SigningKeyUnavailable),
+    };
+
+    token.map_err(|_| AuthError::InvalidToken)
 }
 
 /// Validate a JWT and return the claims.
-pub fn validate_token(token: &str, secret: &str) -> AuthResult<Claims> {
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
-    .map_err(|e| match e.kind() {
-        jsonwebtoken::errors::ErrorKind::ExpiredSignature => crate::AuthError::TokenExpired,
-        _ => crate::AuthError::InvalidToken,
-    })
+pub fn validate_token(token: &str, keys: &KeyMaterial) -> AuthResult<Claims> {
+    let result = match keys {
+        KeyMaterial::Hs256 { secret } => decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &Validation::new(Algorithm::HS256),
+        ),
+        KeyMaterial::Rs256 { public_key, .. } => {
+            decode::<Claims>(token, public_key, &Validation::new(Algorithm::RS256))
+        }
+    };
+
+    result
+        .map(|data| data.claims)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+            _ => AuthError::InvalidToken,
+        })
 }
@@ -0,0 +1,4 @@
+-- Client-supplied nonce so retried sends return the original message instead of a duplicate
+ALTER TABLE messages ADD COLUMN nonce TEXT;
+
+CREATE UNIQUE INDEX idx_messages_nonce ON messages (channel_id, author_id, nonce) WHERE nonce IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Audit log of moderation-relevant server actions
+CREATE TABLE audit_log (
+    id          UUID PRIMARY KEY,
+    server_id   UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    actor_id    UUID NOT NULL REFERENCES users(id),
+    action      TEXT NOT NULL,
+    target      TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_audit_log_server ON audit_log (server_id, id DESC);
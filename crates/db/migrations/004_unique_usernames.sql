@@ -0,0 +1,3 @@
+-- Discriminator-free usernames. Deployments with UNIQUE_USERNAMES enabled store the '0'
+-- sentinel discriminator, and those usernames must be unique regardless of case.
+CREATE UNIQUE INDEX idx_users_unique_username ON users (lower(username)) WHERE discriminator = '0';
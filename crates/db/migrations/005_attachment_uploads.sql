@@ -0,0 +1,4 @@
+-- Attachments are uploaded before the message that references them exists
+ALTER TABLE attachments ALTER COLUMN message_id DROP NOT NULL;
+ALTER TABLE attachments ADD COLUMN uploader_id UUID REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE attachments ADD COLUMN channel_id UUID REFERENCES channels(id) ON DELETE CASCADE;
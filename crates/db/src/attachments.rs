@@ -0,0 +1,112 @@
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::DbResult;
+
+#[derive(Debug, serde::Serialize, FromRow)]
+pub struct AttachmentRow {
+    pub id: Uuid,
+    pub message_id: Option<Uuid>,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub storage_path: String,
+    pub iv: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub uploader_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+}
+
+/// Record an uploaded file. It stays unattached until `link_attachments` assigns it a message.
+pub async fn create_attachment(
+    pool: &PgPool,
+    uploader_id: Uuid,
+    channel_id: Uuid,
+    filename: &str,
+    content_type: &str,
+    size: i64,
+    storage_path: &str,
+) -> DbResult<AttachmentRow> {
+    let id = Uuid::now_v7();
+
+    let row: AttachmentRow = sqlx::query_as(
+        "INSERT INTO attachments (id, uploader_id, channel_id, filename, content_type, size, storage_path) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+    )
+    .bind(id)
+    .bind(uploader_id)
+    .bind(channel_id)
+    .bind(filename)
+    .bind(content_type)
+    .bind(size)
+    .bind(storage_path)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn find_by_id(pool: &PgPool, id: Uuid) -> DbResult<AttachmentRow> {
+    let row: Option<AttachmentRow> = sqlx::query_as("SELECT * FROM attachments WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
+/// Attach uploads to a message, all or nothing. Only unattached uploads by `uploader_id` to
+/// `channel_id` match; if any id doesn't (unknown, someone else's, or claimed by a concurrent
+/// send) nothing is linked and `DbError::NotFound` is returned. `ids` must not contain
+/// duplicates.
+pub async fn link_attachments(
+    pool: &PgPool,
+    ids: &[Uuid],
+    message_id: Uuid,
+    uploader_id: Uuid,
+    channel_id: Uuid,
+) -> DbResult<Vec<AttachmentRow>> {
+    let mut tx = pool.begin().await?;
+
+    let rows: Vec<AttachmentRow> = sqlx::query_as(
+        "UPDATE attachments SET message_id = $1 WHERE id = ANY($2) AND uploader_id = $3 AND channel_id = $4 AND message_id IS NULL RETURNING *",
+    )
+    .bind(message_id)
+    .bind(ids)
+    .bind(uploader_id)
+    .bind(channel_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if rows.len() != ids.len() {
+        tx.rollback().await?;
+        return Err(crate::DbError::NotFound);
+    }
+    tx.commit().await?;
+
+    Ok(rows)
+}
+
+/// Distinct storage paths of every attachment in a channel, linked or still pending. The rows
+/// cascade away with the channel, so collect these before deleting it.
+pub async fn channel_storage_paths(pool: &PgPool, channel_id: Uuid) -> DbResult<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT DISTINCT a.storage_path FROM attachments a LEFT JOIN messages m ON m.id = a.message_id WHERE a.channel_id = $1 OR m.channel_id = $1",
+    )
+    .bind(channel_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(path,)| path).collect())
+}
+
+/// Whether any attachment still references a stored file. Identical uploads share a path, so
+/// a file may only be deleted once this is false.
+pub async fn path_in_use(pool: &PgPool, storage_path: &str) -> DbResult<bool> {
+    let row: (bool,) =
+        sqlx::query_as("SELECT EXISTS(SELECT 1 FROM attachments WHERE storage_path = $1)")
+            .bind(storage_path)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(row.0)
+}
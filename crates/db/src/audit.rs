@@ -0,0 +1,84 @@
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::DbResult;
+
+/// Server actions recorded in the audit log, stored as snake_case text in `audit_log.action`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    ChannelCreate,
+    ChannelDelete,
+    InviteCreate,
+    RoleUpdate,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::ChannelCreate => "channel_create",
+            AuditAction::ChannelDelete => "channel_delete",
+            AuditAction::InviteCreate => "invite_create",
+            AuditAction::RoleUpdate => "role_update",
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, FromRow)]
+pub struct AuditLogRow {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub actor_id: Uuid,
+    pub action: String,
+    /// Id of the affected entity (channel id, invite code, user id, ...).
+    pub target: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn record(
+    pool: &PgPool,
+    server_id: Uuid,
+    actor_id: Uuid,
+    action: AuditAction,
+    target: Option<&str>,
+) -> DbResult<AuditLogRow> {
+    let id = Uuid::now_v7();
+
+    let row: AuditLogRow = sqlx::query_as(
+        "INSERT INTO audit_log (id, server_id, actor_id, action, target) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+    )
+    .bind(id)
+    .bind(server_id)
+    .bind(actor_id)
+    .bind(action.as_str())
+    .bind(target)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn fetch_entries(
+    pool: &PgPool,
+    server_id: Uuid,
+    before: Option<Uuid>,
+    limit: i64,
+) -> DbResult<Vec<AuditLogRow>> {
+    let rows: Vec<AuditLogRow> = if let Some(before) = before {
+        sqlx::query_as(
+            "SELECT * FROM audit_log WHERE server_id = $1 AND id < $2 ORDER BY id DESC LIMIT $3",
+        )
+        .bind(server_id)
+        .bind(before)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+    } else {
+        sqlx::query_as("SELECT * FROM audit_log WHERE server_id = $1 ORDER BY id DESC LIMIT $2")
+            .bind(server_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+    };
+
+    Ok(rows)
+}
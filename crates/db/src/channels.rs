@@ -35,6 +35,15 @@ pub async fn create_channel(
     Ok(row)
 }
 
+pub async fn find_by_id(pool: &PgPool, id: Uuid) -> DbResult<ChannelRow> {
+    let row: Option<ChannelRow> = sqlx::query_as("SELECT * FROM channels WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
 pub async fn fetch_server_channels(pool: &PgPool, server_id: Uuid) -> DbResult<Vec<ChannelRow>> {
     let rows: Vec<ChannelRow> =
         sqlx::query_as("SELECT * FROM channels WHERE server_id = $1 ORDER BY position")
@@ -44,3 +53,19 @@ pub async fn fetch_server_channels(pool: &PgPool, server_id: Uuid) -> DbResult<V
 
     Ok(rows)
 }
+
+/// Delete a channel. Its messages, reactions and attachment rows go with it via FK cascades;
+/// invites pointing at it are kept with their channel cleared. Stored files are not touched:
+/// callers collect `attachments::channel_storage_paths` first and remove the unreferenced ones.
+pub async fn delete_channel(pool: &PgPool, id: Uuid, server_id: Uuid) -> DbResult<()> {
+    let result = sqlx::query("DELETE FROM channels WHERE id = $1 AND server_id = $2")
+        .bind(id)
+        .bind(server_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::DbError::NotFound);
+    }
+    Ok(())
+}
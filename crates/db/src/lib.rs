@@ -7,6 +7,10 @@ pub mod servers;
 pub mod channels;
 pub mod members;
 pub mod invites;
+pub mod audit;
+pub mod reactions;
+pub mod attachments;
+pub mod roles;
 
 #[derive(Debug, Error)]
 pub enum DbError {
@@ -20,6 +24,14 @@ pub enum DbError {
 
 pub type DbResult<T> = Result<T, DbError>;
 
+/// Map unique-constraint violations to `DbError::AlreadyExists`; pass everything else through.
+pub(crate) fn unique_violation(e: sqlx::Error) -> DbError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => DbError::AlreadyExists,
+        _ => DbError::Sqlx(e),
+    }
+}
+
 /// Create a connection pool from a database URL.
 pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
     let pool = PgPool::connect(database_url).await?;
@@ -11,6 +11,17 @@ pub struct MemberRow {
     pub joined_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// A member joined with their public user fields, as returned by `search_members`.
+#[derive(Debug, serde::Serialize, FromRow)]
+pub struct MemberSearchRow {
+    pub user_id: Uuid,
+    pub username: String,
+    pub discriminator: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub nickname: Option<String>,
+}
+
 pub async fn is_member(pool: &PgPool, server_id: Uuid, user_id: Uuid) -> DbResult<bool> {
     let row: (bool,) = sqlx::query_as(
         "SELECT EXISTS(SELECT 1 FROM members WHERE server_id = $1 AND user_id = $2)",
@@ -36,6 +47,34 @@ pub async fn add_member(pool: &PgPool, server_id: Uuid, user_id: Uuid) -> DbResu
     Ok(row)
 }
 
+/// Case-insensitive prefix search over a server's members by username, display name, or
+/// nickname. Results are ordered by username; pass the last `user_id` as `after` for the next page.
+pub async fn search_members(
+    pool: &PgPool,
+    server_id: Uuid,
+    query: &str,
+    after: Option<Uuid>,
+    limit: i64,
+) -> DbResult<Vec<MemberSearchRow>> {
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("{escaped}%");
+
+    let rows: Vec<MemberSearchRow> = sqlx::query_as(
+        "SELECT u.id AS user_id, u.username, u.discriminator, u.display_name, u.avatar_url, m.nickname FROM members m INNER JOIN users u ON u.id = m.user_id WHERE m.server_id = $1 AND (u.username ILIKE $2 OR u.display_name ILIKE $2 OR m.nickname ILIKE $2) AND ($3::uuid IS NULL OR (u.username, u.id) > (SELECT username, id FROM users WHERE id = $3)) ORDER BY u.username, u.id LIMIT $4",
+    )
+    .bind(server_id)
+    .bind(pattern)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
 /// Get all channel IDs a user has access to (via their server memberships).
 pub async fn user_channel_ids(pool: &PgPool, user_id: Uuid) -> DbResult<Vec<Uuid>> {
     let rows: Vec<(Uuid,)> = sqlx::query_as(
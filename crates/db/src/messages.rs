@@ -13,35 +13,99 @@ pub struct MessageRow {
     pub pinned: bool,
     pub edited_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub nonce: Option<String>,
 }
 
+/// Insert a message. Returns `DbError::AlreadyExists` if the author already sent a message
+/// with the same nonce to this channel; use `find_by_nonce` to fetch the original.
 pub async fn create_message(
     pool: &PgPool,
     channel_id: Uuid,
     author_id: Uuid,
     content: Option<&str>,
     replies_to: Option<Uuid>,
+    nonce: Option<&str>,
 ) -> DbResult<MessageRow> {
     let id = Uuid::now_v7();
 
     let row: MessageRow = sqlx::query_as(
-        "INSERT INTO messages (id, channel_id, author_id, content, replies_to) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        "INSERT INTO messages (id, channel_id, author_id, content, replies_to, nonce) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
     )
     .bind(id)
     .bind(channel_id)
     .bind(author_id)
     .bind(content)
     .bind(replies_to)
+    .bind(nonce)
     .fetch_one(pool)
-    .await?;
+    .await
+    .map_err(crate::unique_violation)?;
 
     Ok(row)
 }
 
+pub async fn find_by_nonce(
+    pool: &PgPool,
+    channel_id: Uuid,
+    author_id: Uuid,
+    nonce: &str,
+) -> DbResult<MessageRow> {
+    let row: Option<MessageRow> = sqlx::query_as(
+        "SELECT * FROM messages WHERE channel_id = $1 AND author_id = $2 AND nonce = $3",
+    )
+    .bind(channel_id)
+    .bind(author_id)
+    .bind(nonce)
+    .fetch_optional(pool)
+    .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
+pub async fn find_by_id(pool: &PgPool, id: Uuid, channel_id: Uuid) -> DbResult<MessageRow> {
+    let row: Option<MessageRow> =
+        sqlx::query_as("SELECT * FROM messages WHERE id = $1 AND channel_id = $2")
+            .bind(id)
+            .bind(channel_id)
+            .fetch_optional(pool)
+            .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
+/// Replace a message's content and set `edited_at`. Matches only if `author_id` wrote it.
+pub async fn update_message(
+    pool: &PgPool,
+    id: Uuid,
+    channel_id: Uuid,
+    author_id: Uuid,
+    content: &str,
+) -> DbResult<MessageRow> {
+    let row: Option<MessageRow> = sqlx::query_as(
+        "UPDATE messages SET content = $4, edited_at = now() WHERE id = $1 AND channel_id = $2 AND author_id = $3 RETURNING *",
+    )
+    .bind(id)
+    .bind(channel_id)
+    .bind(author_id)
+    .bind(content)
+    .fetch_optional(pool)
+    .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
+/// Page through a channel's messages.
+///
+/// - `before`: messages older than this id, newest first (scrolling back through history)
+/// - `after`: messages newer than this id, oldest first (catching up after a reconnect)
+/// - neither: the latest messages, newest first
+///
+/// If both cursors are given, `before` wins.
 pub async fn fetch_messages(
     pool: &PgPool,
     channel_id: Uuid,
     before: Option<Uuid>,
+    after: Option<Uuid>,
     limit: i64,
 ) -> DbResult<Vec<MessageRow>> {
     let rows: Vec<MessageRow> = if let Some(before) = before {
@@ -53,6 +117,15 @@ pub async fn fetch_messages(
         .bind(limit)
         .fetch_all(pool)
         .await?
+    } else if let Some(after) = after {
+        sqlx::query_as(
+            "SELECT * FROM messages WHERE channel_id = $1 AND id > $2 ORDER BY id ASC LIMIT $3",
+        )
+        .bind(channel_id)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
     } else {
         sqlx::query_as("SELECT * FROM messages WHERE channel_id = $1 ORDER BY id DESC LIMIT $2")
             .bind(channel_id)
@@ -0,0 +1,62 @@
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::DbResult;
+
+#[derive(Debug, serde::Serialize, FromRow)]
+pub struct ReactionRow {
+    pub message_id: Uuid,
+    pub user_id: Uuid,
+    pub emoji: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Add a reaction. Returns `DbError::AlreadyExists` if the user already reacted with this emoji.
+pub async fn add_reaction(
+    pool: &PgPool,
+    message_id: Uuid,
+    user_id: Uuid,
+    emoji: &str,
+) -> DbResult<ReactionRow> {
+    let row: ReactionRow = sqlx::query_as(
+        "INSERT INTO reactions (message_id, user_id, emoji) VALUES ($1, $2, $3) RETURNING *",
+    )
+    .bind(message_id)
+    .bind(user_id)
+    .bind(emoji)
+    .fetch_one(pool)
+    .await
+    .map_err(crate::unique_violation)?;
+
+    Ok(row)
+}
+
+pub async fn remove_reaction(
+    pool: &PgPool,
+    message_id: Uuid,
+    user_id: Uuid,
+    emoji: &str,
+) -> DbResult<()> {
+    let result =
+        sqlx::query("DELETE FROM reactions WHERE message_id = $1 AND user_id = $2 AND emoji = $3")
+            .bind(message_id)
+            .bind(user_id)
+            .bind(emoji)
+            .execute(pool)
+            .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::DbError::NotFound);
+    }
+    Ok(())
+}
+
+pub async fn list_reactions(pool: &PgPool, message_id: Uuid) -> DbResult<Vec<ReactionRow>> {
+    let rows: Vec<ReactionRow> =
+        sqlx::query_as("SELECT * FROM reactions WHERE message_id = $1 ORDER BY created_at")
+            .bind(message_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows)
+}
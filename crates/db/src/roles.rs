@@ -0,0 +1,109 @@
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::DbResult;
+
+#[derive(Debug, serde::Serialize, FromRow)]
+pub struct RoleRow {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub name: String,
+    pub color: Option<i32>,
+    pub permissions: i64,
+    pub position: i32,
+}
+
+pub async fn create_role(
+    pool: &PgPool,
+    server_id: Uuid,
+    name: &str,
+    permissions: i64,
+) -> DbResult<RoleRow> {
+    let id = Uuid::now_v7();
+
+    let row: RoleRow = sqlx::query_as(
+        "INSERT INTO roles (id, server_id, name, permissions) VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(id)
+    .bind(server_id)
+    .bind(name)
+    .bind(permissions)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn find_by_id(pool: &PgPool, id: Uuid, server_id: Uuid) -> DbResult<RoleRow> {
+    let row: Option<RoleRow> =
+        sqlx::query_as("SELECT * FROM roles WHERE id = $1 AND server_id = $2")
+            .bind(id)
+            .bind(server_id)
+            .fetch_optional(pool)
+            .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
+pub async fn fetch_server_roles(pool: &PgPool, server_id: Uuid) -> DbResult<Vec<RoleRow>> {
+    let rows: Vec<RoleRow> =
+        sqlx::query_as("SELECT * FROM roles WHERE server_id = $1 ORDER BY position, id")
+            .bind(server_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows)
+}
+
+/// Give a member a role. Returns `DbError::AlreadyExists` if they already have it.
+pub async fn assign_role(
+    pool: &PgPool,
+    server_id: Uuid,
+    user_id: Uuid,
+    role_id: Uuid,
+) -> DbResult<()> {
+    sqlx::query("INSERT INTO member_roles (server_id, user_id, role_id) VALUES ($1, $2, $3)")
+        .bind(server_id)
+        .bind(user_id)
+        .bind(role_id)
+        .execute(pool)
+        .await
+        .map_err(crate::unique_violation)?;
+
+    Ok(())
+}
+
+pub async fn unassign_role(
+    pool: &PgPool,
+    server_id: Uuid,
+    user_id: Uuid,
+    role_id: Uuid,
+) -> DbResult<()> {
+    let result = sqlx::query(
+        "DELETE FROM member_roles WHERE server_id = $1 AND user_id = $2 AND role_id = $3",
+    )
+    .bind(server_id)
+    .bind(user_id)
+    .bind(role_id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::DbError::NotFound);
+    }
+    Ok(())
+}
+
+/// Union of the permission bits of every role the member has. Does not account for
+/// server ownership; callers handle that.
+pub async fn member_permissions(pool: &PgPool, server_id: Uuid, user_id: Uuid) -> DbResult<i64> {
+    let row: (i64,) = sqlx::query_as(
+        "SELECT COALESCE(bit_or(r.permissions), 0) FROM member_roles mr INNER JOIN roles r ON r.id = mr.role_id WHERE mr.server_id = $1 AND mr.user_id = $2",
+    )
+    .bind(server_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.0)
+}
@@ -46,6 +46,15 @@ pub async fn create_server(pool: &PgPool, name: &str, owner_id: Uuid) -> DbResul
     Ok(row)
 }
 
+pub async fn find_by_id(pool: &PgPool, id: Uuid) -> DbResult<ServerRow> {
+    let row: Option<ServerRow> = sqlx::query_as("SELECT * FROM servers WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
+
 pub async fn fetch_user_servers(pool: &PgPool, user_id: Uuid) -> DbResult<Vec<ServerRow>> {
     let rows: Vec<ServerRow> = sqlx::query_as(
         "SELECT s.* FROM servers s INNER JOIN members m ON m.server_id = s.id WHERE m.user_id = $1 ORDER BY s.created_at",
@@ -18,14 +18,25 @@ pub struct UserRow {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Discriminator stored for users created with unique usernames enabled.
+pub const NO_DISCRIMINATOR: &str = "0";
+
+/// Create a user. With `unique_username`, the user gets `NO_DISCRIMINATOR` and the username
+/// must be globally unique (case-insensitive); otherwise a random 4-digit discriminator is
+/// assigned. Returns `DbError::AlreadyExists` if the username or email is taken.
 pub async fn create_user(
     pool: &PgPool,
     username: &str,
     email: &str,
     password_hash: &str,
+    unique_username: bool,
 ) -> DbResult<UserRow> {
     let id = Uuid::now_v7();
-    let disc = format!("{:04}", rand::random::<u16>() % 10000);
+    let disc = if unique_username {
+        NO_DISCRIMINATOR.to_string()
+    } else {
+        format!("{:04}", rand::random::<u16>() % 10000)
+    };
 
     let row: UserRow = sqlx::query_as(
         "INSERT INTO users (id, username, discriminator, email, password_hash) VALUES ($1, $2, $3, $4, $5) RETURNING *",
@@ -36,7 +47,8 @@ pub async fn create_user(
     .bind(email)
     .bind(password_hash)
     .fetch_one(pool)
-    .await?;
+    .await
+    .map_err(crate::unique_violation)?;
 
     Ok(row)
 }
@@ -58,3 +70,21 @@ pub async fn find_by_email(pool: &PgPool, email: &str) -> DbResult<UserRow> {
 
     row.ok_or(crate::DbError::NotFound)
 }
+
+/// Look up a user by username (case-insensitive) and discriminator. Pass `NO_DISCRIMINATOR`
+/// for users created with unique usernames.
+pub async fn find_by_username(
+    pool: &PgPool,
+    username: &str,
+    discriminator: &str,
+) -> DbResult<UserRow> {
+    let row: Option<UserRow> = sqlx::query_as(
+        "SELECT * FROM users WHERE lower(username) = lower($1) AND discriminator = $2",
+    )
+    .bind(username)
+    .bind(discriminator)
+    .fetch_optional(pool)
+    .await?;
+
+    row.ok_or(crate::DbError::NotFound)
+}
@@ -0,0 +1,46 @@
+use std::io::Write;
+
+use axum::extract::ws::Message;
+use flate2::{Compression, write::GzEncoder};
+
+/// Payloads shorter than this are sent as plain text even when compression is negotiated;
+/// the gzip header outweighs the savings on small frames like `Pong` or `TypingStart`.
+const MIN_COMPRESS_LEN: usize = 1024;
+
+/// Frame encoding negotiated by the client at connect time (`/?compress=gzip`).
+///
+/// axum's WebSocket layer does not negotiate permessage-deflate, so compression is done per
+/// frame: gzipped payloads go out as `Binary` frames, everything else as `Text`. The gateway
+/// never sends binary frames otherwise, so the frame type is the compression marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Plain,
+    Gzip,
+}
+
+impl Encoding {
+    pub fn from_query(value: Option<&str>) -> Self {
+        match value {
+            Some("gzip") => Encoding::Gzip,
+            _ => Encoding::Plain,
+        }
+    }
+
+    /// Wrap a serialized `ServerEvent` in a WebSocket frame.
+    pub fn encode(self, payload: String) -> Message {
+        if self == Encoding::Gzip && payload.len() >= MIN_COMPRESS_LEN {
+            match gzip(payload.as_bytes()) {
+                Ok(compressed) => return Message::Binary(compressed.into()),
+                Err(e) => tracing::warn!("gzip failed, sending uncompressed: {e}"),
+            }
+        }
+        Message::Text(payload.into())
+    }
+}
+
+fn gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(data.len() / 2), Compression::fast());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
@@ -1,28 +1,71 @@
-use std::{env, sync::Arc};
+use std::{
+    collections::HashSet,
+    env,
+    sync::{Arc, atomic::Ordering},
+    time::Duration,
+};
 
 use axum::{
     Router,
+    body::Bytes,
     extract::{
-        State, WebSocketUpgrade,
-        ws::{Message, WebSocket},
+        Query, State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket},
     },
     response::IntoResponse,
     routing::get,
 };
 use fred::{
+    clients::Client as RedisClient,
     interfaces::{ClientLike, EventInterface, PubsubInterface},
     types::{Builder, config::Config as RedisConfig},
 };
 use futures::{SinkExt, StreamExt};
-use rusteze_models::{ClientEvent, ServerEvent};
+use rusteze_auth::token::KeyMaterial;
+use rusteze_models::{ClientEvent, ServerEvent, UserStatus};
+use serde::Deserialize;
 use sqlx::PgPool;
-use tokio::sync::broadcast;
+use tokio::{sync::broadcast, time::Instant};
+use tracing::Instrument;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod compress;
+mod metrics;
+mod presence;
+mod ratelimit;
+
+use compress::Encoding;
+use metrics::Metrics;
+use ratelimit::{RateLimitConfig, TokenBucket, Verdict};
+
+/// Most messages replayed per channel when a client reconnects with `last_event_id`.
+/// A channel that hits the limit is followed by `CatchUpTruncated`, telling the client to page
+/// the rest over REST.
+const CATCH_UP_LIMIT: i64 = 100;
+
+/// Redis events buffered per connection while its socket is busy. Sized to absorb a full
+/// reconnect catch-up on a typical account; beyond that the client is told to resync.
+const BRIDGE_CAPACITY: usize = 1024;
+
+/// WebSocket close code sent when a connection keeps exceeding its inbound event limit.
+const CLOSE_RATE_LIMITED: u16 = 4008;
+
+/// WebSocket close code sent when a connection goes quiet for longer than the heartbeat timeout.
+const CLOSE_HEARTBEAT_TIMEOUT: u16 = 4009;
+
+/// Protocol-level pings that may go unanswered before the connection is considered dead.
+const MAX_MISSED_PONGS: u32 = 2;
+
 struct GatewayState {
-    jwt_secret: String,
+    jwt_keys: KeyMaterial,
     redis_url: String,
+    /// Shared client for commands and publishing; each connection gets its own subscriber.
+    redis: RedisClient,
     db: PgPool,
+    metrics: Metrics,
+    rate_limit: RateLimitConfig,
+    /// Close connections that send nothing for this long (`GATEWAY_HEARTBEAT_TIMEOUT_SECS`).
+    heartbeat_timeout: Duration,
 }
 
 #[tokio::main]
@@ -38,18 +81,47 @@ async fn main() {
         .init();
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let jwt_secret = env::var("JWT_SECRET").unwrap_or_else(|_| "dev-secret-change-me".into());
+    let jwt_keys = KeyMaterial::from_env().expect("invalid JWT configuration");
     let redis_url = env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
     let bind = env::var("GATEWAY_BIND").unwrap_or_else(|_| "0.0.0.0:14703".into());
+    let metrics_bind = env::var("METRICS_BIND").unwrap_or_else(|_| "0.0.0.0:14706".into());
+    let rate_limit = RateLimitConfig::from_env();
+    let heartbeat_timeout = Duration::from_secs(
+        env::var("GATEWAY_HEARTBEAT_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&secs: &u64| secs > 0)
+            .unwrap_or(60),
+    );
 
     let db = rusteze_db::connect(&database_url)
         .await
         .expect("failed to connect to database");
 
+    let redis_config = RedisConfig::from_url(&redis_url).expect("invalid REDIS_URL");
+    let redis = Builder::from_config(redis_config)
+        .build()
+        .expect("failed to build redis client");
+    redis.init().await.expect("failed to connect to Redis");
+
     let state = Arc::new(GatewayState {
-        jwt_secret,
+        jwt_keys,
         redis_url,
+        redis,
         db,
+        metrics: Metrics::default(),
+        rate_limit,
+        heartbeat_timeout,
+    });
+
+    // Metrics get their own listener so scrapes never compete with WebSocket upgrades
+    let metrics_app = Router::new()
+        .route("/metrics", get(metrics::metrics_handler))
+        .with_state(state.clone());
+    let metrics_listener = tokio::net::TcpListener::bind(&metrics_bind).await.unwrap();
+    tracing::info!("metrics listening on {metrics_bind}");
+    tokio::spawn(async move {
+        axum::serve(metrics_listener, metrics_app).await.unwrap();
     });
 
     let app = Router::new()
@@ -61,45 +133,101 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
+/// Publish an event to a Redis topic for fan-out to every gateway. Best-effort: failures are
+/// only logged.
+async fn publish_event(redis: &RedisClient, topic: &str, event: &ServerEvent) {
+    let Ok(payload) = serde_json::to_string(event) else {
+        return;
+    };
+    let result: Result<(), _> = redis.publish(topic, payload.as_str()).await;
+    if let Err(e) = result {
+        tracing::warn!("failed to publish to {topic}: {e}");
+    }
+}
+
+/// Whether the user is a member of the server that owns the channel. Lookup failures deny.
+async fn can_access_channel(db: &PgPool, user_id: uuid::Uuid, channel_id: uuid::Uuid) -> bool {
+    match rusteze_db::members::channel_server_id(db, channel_id).await {
+        Ok(Some(server_id)) => rusteze_db::members::is_member(db, server_id, user_id)
+            .await
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+#[derive(Deserialize)]
+struct ConnectParams {
+    compress: Option<String>,
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<ConnectParams>,
     State(state): State<Arc<GatewayState>>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    let encoding = Encoding::from_query(params.compress.as_deref());
+    let connection_id = uuid::Uuid::now_v7();
+    let span = tracing::info_span!("connection", %connection_id, user_id = tracing::field::Empty);
+    ws.on_upgrade(move |socket| {
+        handle_socket(socket, state, encoding, connection_id).instrument(span)
+    })
 }
 
-async fn handle_socket(socket: WebSocket, state: Arc<GatewayState>) {
+async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<GatewayState>,
+    encoding: Encoding,
+    connection_id: uuid::Uuid,
+) {
+    let _connection = state.metrics.connection();
     let (mut sink, mut stream) = socket.split();
 
-    // Wait for Authenticate message
-    let user_id = loop {
-        match stream.next().await {
-            Some(Ok(Message::Text(text))) => {
-                if let Ok(event) = serde_json::from_str::<ClientEvent>(&text) {
-                    match event {
-                        ClientEvent::Authenticate { token } => {
-                            match rusteze_auth::token::validate_token(&token, &state.jwt_secret) {
-                                Ok(claims) => break claims.sub,
-                                Err(_) => {
-                                    let _ = sink.close().await;
-                                    return;
+    // Wait for Authenticate message. A client that never sends one would otherwise hold the
+    // socket open indefinitely, so it gets the same deadline as a missed heartbeat.
+    let authenticated = tokio::time::timeout(state.heartbeat_timeout, async {
+        loop {
+            match stream.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    if let Ok(event) = serde_json::from_str::<ClientEvent>(&text) {
+                        match event {
+                            ClientEvent::Authenticate {
+                                token,
+                                last_event_id,
+                            } => {
+                                match rusteze_auth::token::validate_token(&token, &state.jwt_keys) {
+                                    Ok(claims) => break Some((claims.sub, last_event_id)),
+                                    Err(_) => {
+                                        let _ = sink.close().await;
+                                        return None;
+                                    }
                                 }
                             }
+                            ClientEvent::Ping { ts } => {
+                                let pong = serde_json::to_string(&ServerEvent::Pong { ts }).unwrap();
+                                let _ = sink.send(encoding.encode(pong)).await;
+                            }
+                            _ => {}
                         }
-                        ClientEvent::Ping { ts } => {
-                            let pong = serde_json::to_string(&ServerEvent::Pong { ts }).unwrap();
-                            let _ = sink.send(Message::Text(pong.into())).await;
-                        }
-                        _ => {}
                     }
                 }
+                Some(Ok(Message::Close(_))) | None => return None,
+                _ => {}
             }
-            Some(Ok(Message::Close(_))) | None => return,
-            _ => {}
+        }
+    })
+    .await;
+    let (user_id, last_event_id) = match authenticated {
+        Ok(Some(identity)) => identity,
+        Ok(None) => return,
+        Err(_) => {
+            tracing::debug!("no Authenticate before timeout, closing");
+            let _ = sink.close().await;
+            return;
         }
     };
 
-    tracing::info!("user {user_id} authenticated on gateway");
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    tracing::info!("authenticated on gateway");
 
     // Load user's data for Ready event
     let servers = rusteze_db::servers::fetch_user_servers(&state.db, user_id)
@@ -137,7 +265,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<GatewayState>) {
     };
 
     let ready_json = serde_json::to_string(&ready).unwrap();
-    if sink.send(Message::Text(ready_json.into())).await.is_err() {
+    if sink.send(encoding.encode(ready_json)).await.is_err() {
         return;
     }
 
@@ -158,22 +286,49 @@ async fn handle_socket(socket: WebSocket, state: Arc<GatewayState>) {
     // Subscribe to user's personal channel
     let _ = subscriber.subscribe(format!("user:{user_id}")).await;
 
-    // Subscribe to all channels the user has access to
+    // Subscribe to all channels the user has access to. Only these `channel:` topics can be
+    // added or dropped later; `user:` and `server:` topics last as long as the connection.
+    let mut channels: HashSet<uuid::Uuid> = HashSet::new();
+    // Channels whose ChannelDelete was already forwarded, to drop the second copy
+    let mut deleted_channels: HashSet<uuid::Uuid> = HashSet::new();
     for ch_id in &channel_ids {
         let _ = subscriber.subscribe(format!("channel:{ch_id}")).await;
+        channels.insert(*ch_id);
+    }
+
+    // Server-wide topics carry presence for everyone sharing a server
+    let server_ids: Vec<uuid::Uuid> = servers.iter().map(|s| s.id).collect();
+    for server_id in &server_ids {
+        let _ = subscriber.subscribe(format!("server:{server_id}")).await;
     }
+    let fixed_subscriptions = 1 + server_ids.len() as u64;
+    state
+        .metrics
+        .subscriptions
+        .fetch_add(fixed_subscriptions + channels.len() as u64, Ordering::Relaxed);
 
     tracing::info!(
-        "user {user_id} subscribed to {} channels",
-        channel_ids.len()
+        channels = channel_ids.len(),
+        servers = server_ids.len(),
+        "subscribed to channels"
+    );
+
+    let (status, newly_online) = presence::connect(&state.redis, user_id, connection_id).await;
+    if newly_online {
+        presence::announce(&state.redis, &server_ids, user_id, status).await;
+    }
+    let mut presence_refresh = tokio::time::interval_at(
+        Instant::now() + presence::PRESENCE_REFRESH,
+        presence::PRESENCE_REFRESH,
     );
 
     // Bridge Redis -> WebSocket via broadcast channel
-    let (tx, mut rx) = broadcast::channel::<String>(256);
+    let (tx, mut rx) = broadcast::channel::<String>(BRIDGE_CAPACITY);
 
     let mut message_rx = subscriber.message_rx();
+    let mut reconnect_rx = subscriber.reconnect_rx();
     let tx_clone = tx.clone();
-    tokio::spawn(async move {
+    let forwarder = tokio::spawn(async move {
         while let Ok(msg) = message_rx.recv().await {
             if let Ok(payload) = msg.value.convert::<String>() {
                 let _ = tx_clone.send(payload);
@@ -181,46 +336,190 @@ async fn handle_socket(socket: WebSocket, state: Arc<GatewayState>) {
         }
     });
 
+    // Replay what the client missed while disconnected. Runs once the Redis bridge is up so
+    // nothing falls in between. Live events published meanwhile queue in the broadcast buffer
+    // and may arrive twice, which clients handle by message id; if more than BRIDGE_CAPACITY
+    // queue up, the oldest are dropped and the client is sent `Resync`.
+    if let Some(last_event_id) = last_event_id {
+        // A failed send breaks out; the main loop then sees the closed socket and cleans up
+        'catch_up: for channel_id in &channel_ids {
+            let missed = rusteze_db::messages::fetch_messages(
+                &state.db,
+                *channel_id,
+                None,
+                Some(last_event_id),
+                CATCH_UP_LIMIT,
+            )
+            .await
+            .unwrap_or_default();
+            let truncated_after = match missed.last() {
+                Some(last) if missed.len() == CATCH_UP_LIMIT as usize => Some(last.id),
+                _ => None,
+            };
+
+            for msg in missed {
+                // Attachments aren't joined here; clients fetch them over REST if needed
+                let event = ServerEvent::MessageCreate(rusteze_models::Message {
+                    id: msg.id,
+                    channel_id: msg.channel_id,
+                    author_id: msg.author_id,
+                    content: msg.content,
+                    attachments: vec![],
+                    embeds: vec![],
+                    mentions: vec![],
+                    replies_to: msg.replies_to,
+                    pinned: msg.pinned,
+                    edited_at: msg.edited_at,
+                    created_at: msg.created_at,
+                });
+                let payload = serde_json::to_string(&event).unwrap();
+                if sink.send(encoding.encode(payload)).await.is_err() {
+                    break 'catch_up;
+                }
+            }
+
+            if let Some(after) = truncated_after {
+                let event = ServerEvent::CatchUpTruncated {
+                    channel_id: *channel_id,
+                    after,
+                };
+                let payload = serde_json::to_string(&event).unwrap();
+                if sink.send(encoding.encode(payload)).await.is_err() {
+                    break 'catch_up;
+                }
+            }
+        }
+    }
+
+    let mut bucket = TokenBucket::new(state.rate_limit);
+
+    // Any inbound frame counts as a heartbeat; check twice per timeout window. Each check also
+    // sends a WebSocket ping frame, which standard clients answer without app code.
+    let mut last_seen = Instant::now();
+    let mut missed_pongs: u32 = 0;
+    let mut heartbeat = tokio::time::interval(state.heartbeat_timeout / 2);
+
     // Main event loop
     loop {
         tokio::select! {
             // Outbound: Redis -> Client
-            Ok(payload) = rx.recv() => {
-                if sink.send(Message::Text(payload.into())).await.is_err() {
+            received = rx.recv() => {
+                let payload = match received {
+                    Ok(payload) => payload,
+                    // The bridge outran this socket (e.g. during a large catch-up) and events
+                    // were dropped; tell the client to refetch over REST rather than hide the gap
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "connection lagged behind redis events");
+                        let resync = serde_json::to_string(&ServerEvent::Resync { missed }).unwrap();
+                        if sink.send(encoding.encode(resync)).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                // A deleted channel's topic goes quiet for good; drop it. The event is published
+                // to both the channel and its server, so only the first copy is forwarded. The
+                // substring check skips a full parse for every other event.
+                if payload.contains("\"ChannelDelete\"")
+                    && let Ok(ServerEvent::ChannelDelete { id }) = serde_json::from_str(&payload)
+                {
+                    if !deleted_channels.insert(id) {
+                        continue;
+                    }
+                    if channels.remove(&id) {
+                        let _ = subscriber.unsubscribe(format!("channel:{id}")).await;
+                        state.metrics.subscriptions.fetch_sub(1, Ordering::Relaxed);
+                        tracing::debug!(channel_id = %id, "unsubscribed from deleted channel");
+                    }
+                }
+                if sink.send(encoding.encode(payload)).await.is_err() {
                     break;
                 }
+                state.metrics.messages_forwarded.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(_) = reconnect_rx.recv() => {
+                state.metrics.redis_reconnects.fetch_add(1, Ordering::Relaxed);
+            }
+            _ = presence_refresh.tick() => {
+                if presence::refresh(&state.redis, user_id, connection_id).await {
+                    presence::announce(&state.redis, &server_ids, user_id, UserStatus::Online).await;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > state.heartbeat_timeout || missed_pongs >= MAX_MISSED_PONGS {
+                    tracing::info!(missed_pongs, "closing connection after heartbeat timeout");
+                    let _ = sink.send(Message::Close(Some(CloseFrame {
+                        code: CLOSE_HEARTBEAT_TIMEOUT,
+                        reason: "heartbeat timeout".into(),
+                    }))).await;
+                    break;
+                }
+                if sink.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break;
+                }
+                missed_pongs += 1;
             }
             // Inbound: Client -> Server
             msg = stream.next() => {
+                last_seen = Instant::now();
                 match msg {
                     Some(Ok(Message::Text(text))) => {
+                        match bucket.check() {
+                            Verdict::Allow => {}
+                            Verdict::Drop => continue,
+                            Verdict::Close => {
+                                tracing::warn!("closing rate-limited connection");
+                                let _ = sink.send(Message::Close(Some(CloseFrame {
+                                    code: CLOSE_RATE_LIMITED,
+                                    reason: "rate limited".into(),
+                                }))).await;
+                                break;
+                            }
+                        }
                         if let Ok(event) = serde_json::from_str::<ClientEvent>(&text) {
                             match event {
                                 ClientEvent::Ping { ts } => {
                                     let pong = serde_json::to_string(&ServerEvent::Pong { ts }).unwrap();
-                                    let _ = sink.send(Message::Text(pong.into())).await;
+                                    let _ = sink.send(encoding.encode(pong)).await;
                                 }
                                 ClientEvent::TypingStart { channel_id } => {
+                                    if !can_access_channel(&state.db, user_id, channel_id).await {
+                                        tracing::debug!(%channel_id, "ignoring typing in inaccessible channel");
+                                        continue;
+                                    }
                                     let event = ServerEvent::TypingStart {
                                         channel_id,
                                         user_id,
                                     };
-                                    if let Ok(payload) = serde_json::to_string(&event) {
-                                        let _: Result<(), _> = PubsubInterface::publish(
-                                            &subscriber,
-                                            format!("channel:{channel_id}"),
-                                            payload.as_str(),
-                                        ).await;
-                                    }
+                                    publish_event(&state.redis, &format!("channel:{channel_id}"), &event).await;
+                                }
+                                ClientEvent::UpdatePresence { status } => {
+                                    let status = presence::set_status(&state.redis, user_id, status).await;
+                                    presence::announce(&state.redis, &server_ids, user_id, status).await;
                                 }
                                 ClientEvent::Subscribe { channel_id } => {
-                                    let _ = subscriber.subscribe(format!("channel:{channel_id}")).await;
-                                    tracing::debug!("user {user_id} subscribed to channel:{channel_id}");
+                                    if !can_access_channel(&state.db, user_id, channel_id).await {
+                                        tracing::debug!(%channel_id, "denied subscribe to inaccessible channel");
+                                        continue;
+                                    }
+                                    if channels.insert(channel_id) {
+                                        let _ = subscriber.subscribe(format!("channel:{channel_id}")).await;
+                                        state.metrics.subscriptions.fetch_add(1, Ordering::Relaxed);
+                                        tracing::debug!(%channel_id, "subscribed to channel");
+                                    }
+                                }
+                                // No-op for channels this connection isn't subscribed to
+                                ClientEvent::Unsubscribe { channel_id } if channels.remove(&channel_id) => {
+                                    let _ = subscriber.unsubscribe(format!("channel:{channel_id}")).await;
+                                    state.metrics.subscriptions.fetch_sub(1, Ordering::Relaxed);
+                                    tracing::debug!(%channel_id, "unsubscribed from channel");
                                 }
                                 _ => {}
                             }
                         }
                     }
+                    Some(Ok(Message::Pong(_))) => missed_pongs = 0,
                     Some(Ok(Message::Close(_))) | None => break,
                     _ => {}
                 }
@@ -228,6 +527,15 @@ async fn handle_socket(socket: WebSocket, state: Arc<GatewayState>) {
         }
     }
 
-    tracing::info!("user {user_id} disconnected from gateway");
+    tracing::info!("disconnected from gateway");
+    // Other tabs or devices may still be connected; only the last one goes offline
+    if presence::disconnect(&state.redis, user_id, connection_id).await {
+        presence::announce(&state.redis, &server_ids, user_id, UserStatus::Offline).await;
+    }
+    state
+        .metrics
+        .subscriptions
+        .fetch_sub(fixed_subscriptions + channels.len() as u64, Ordering::Relaxed);
+    forwarder.abort();
     let _ = subscriber.quit().await;
 }
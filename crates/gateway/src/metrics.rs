@@ -0,0 +1,83 @@
+use std::{
+    fmt::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::GatewayState;
+
+/// Gateway-wide counters, exposed in Prometheus text format on `METRICS_BIND`.
+#[derive(Default)]
+pub struct Metrics {
+    pub connected_clients: AtomicU64,
+    pub subscriptions: AtomicU64,
+    pub messages_forwarded: AtomicU64,
+    pub redis_reconnects: AtomicU64,
+}
+
+impl Metrics {
+    /// Count a connected client until the returned guard is dropped.
+    pub fn connection(&self) -> ConnectionGuard<'_> {
+        self.connected_clients.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard(self)
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let series = [
+            (
+                "connected_clients",
+                "gauge",
+                "Currently connected WebSocket clients.",
+                &self.connected_clients,
+            ),
+            (
+                "subscriptions",
+                "gauge",
+                "Active Redis channel subscriptions across all clients.",
+                &self.subscriptions,
+            ),
+            (
+                "messages_forwarded_total",
+                "counter",
+                "Redis messages forwarded to WebSocket clients.",
+                &self.messages_forwarded,
+            ),
+            (
+                "redis_reconnects_total",
+                "counter",
+                "Redis subscriber reconnections.",
+                &self.redis_reconnects,
+            ),
+        ];
+        for (name, kind, help, value) in series {
+            let _ = writeln!(out, "# HELP rusteze_gateway_{name} {help}");
+            let _ = writeln!(out, "# TYPE rusteze_gateway_{name} {kind}");
+            let _ = writeln!(
+                out,
+                "rusteze_gateway_{name} {}",
+                value.load(Ordering::Relaxed)
+            );
+        }
+        out
+    }
+}
+
+pub struct ConnectionGuard<'a>(&'a Metrics);
+
+impl Drop for ConnectionGuard<'_> {
+    fn drop(&mut self) {
+        self.0.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub async fn metrics_handler(State(state): State<Arc<GatewayState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
@@ -0,0 +1,174 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fred::{
+    clients::Client,
+    interfaces::{KeysInterface, LuaInterface, SortedSetsInterface},
+    types::{Expiration, SetOptions},
+};
+use rusteze_models::{ServerEvent, UserStatus};
+use uuid::Uuid;
+
+/// Lifetime of a user's presence keys. Live connections refresh them every
+/// `PRESENCE_REFRESH`; if every gateway holding the user dies they lapse and the user reads
+/// as offline.
+const PRESENCE_TTL_SECS: i64 = 90;
+
+/// How often a connection refreshes its presence. Well under the TTL so one slow refresh
+/// doesn't flap the user offline.
+pub const PRESENCE_REFRESH: Duration = Duration::from_secs(30);
+
+/// The user's chosen status, shared by all of their connections.
+fn status_key(user_id: Uuid) -> String {
+    format!("presence:{user_id}")
+}
+
+/// Sorted set of the user's live connection ids, scored by last refresh time. Entries older
+/// than the TTL belong to crashed connections and are pruned.
+fn connections_key(user_id: Uuid) -> String {
+    format!("presence:{user_id}:conns")
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+fn log_error<T>(result: Result<T, fred::error::Error>) -> Option<T> {
+    result
+        .inspect_err(|e| tracing::warn!("presence redis error: {e}"))
+        .ok()
+}
+
+async fn touch_connection(redis: &Client, user_id: Uuid, connection_id: Uuid) {
+    let key = connections_key(user_id);
+    log_error::<()>(
+        redis
+            .zadd(
+                &key,
+                None,
+                None,
+                false,
+                false,
+                (now_secs(), connection_id.to_string()),
+            )
+            .await,
+    );
+    log_error::<()>(redis.expire(&key, PRESENCE_TTL_SECS, None).await);
+}
+
+/// Removes connection `ARGV[1]` from set `KEYS[2]`, prunes entries scored at or below
+/// `ARGV[2]`, and deletes status `KEYS[1]` if none are left. Returns 1 if it did. One script so
+/// a reconnect can't register between the count and the delete and lose its status.
+const DISCONNECT_SCRIPT: &str = r#"
+redis.call('ZREM', KEYS[2], ARGV[1])
+redis.call('ZREMRANGEBYSCORE', KEYS[2], '-inf', ARGV[2])
+if redis.call('ZCARD', KEYS[2]) > 0 then
+    return 0
+end
+redis.call('DEL', KEYS[1])
+return 1
+"#;
+
+/// Create the status as `Online` unless it exists. Returns whether it was created.
+async fn create_status(redis: &Client, user_id: Uuid) -> bool {
+    let online = serde_json::to_string(&UserStatus::Online).unwrap_or_default();
+    let created: Option<String> = log_error(
+        redis
+            .set(
+                status_key(user_id),
+                online,
+                Some(Expiration::EX(PRESENCE_TTL_SECS)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await,
+    )
+    .flatten();
+    created.is_some()
+}
+
+/// Register a new connection. Returns the user's current status and whether it is newly
+/// online, in which case the caller should announce it.
+pub async fn connect(redis: &Client, user_id: Uuid, connection_id: Uuid) -> (UserStatus, bool) {
+    touch_connection(redis, user_id, connection_id).await;
+
+    // Only the first connection sets the status; later ones inherit e.g. DoNotDisturb
+    if create_status(redis, user_id).await {
+        return (UserStatus::Online, true);
+    }
+
+    let current: Option<String> = log_error(redis.get(status_key(user_id)).await).flatten();
+    let status = current
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(UserStatus::Online);
+    (status, false)
+}
+
+/// Keep this connection and the user's status alive for another TTL. If the status is gone
+/// while the connection lives (it lapsed, or another connection's disconnect raced this one's
+/// connect) it is recreated as `Online`, and true is returned so the caller announces it.
+pub async fn refresh(redis: &Client, user_id: Uuid, connection_id: Uuid) -> bool {
+    touch_connection(redis, user_id, connection_id).await;
+    if create_status(redis, user_id).await {
+        return true;
+    }
+    log_error::<()>(
+        redis
+            .expire(status_key(user_id), PRESENCE_TTL_SECS, None)
+            .await,
+    );
+    false
+}
+
+/// Store a status chosen by the client for all of the user's connections. Clients can't pick
+/// `Offline` while connected; it is stored as `Invisible`, which others see the same way.
+pub async fn set_status(redis: &Client, user_id: Uuid, status: UserStatus) -> UserStatus {
+    let status = match status {
+        UserStatus::Offline => UserStatus::Invisible,
+        status => status,
+    };
+    let value = serde_json::to_string(&status).unwrap_or_default();
+    log_error::<()>(
+        redis
+            .set(
+                status_key(user_id),
+                value,
+                Some(Expiration::EX(PRESENCE_TTL_SECS)),
+                None,
+                false,
+            )
+            .await,
+    );
+    status
+}
+
+/// Drop a closed connection. Returns true if it was the user's last one, in which case the
+/// status is cleared and the caller should announce `Offline`.
+pub async fn disconnect(redis: &Client, user_id: Uuid, connection_id: Uuid) -> bool {
+    let cutoff = now_secs() - PRESENCE_TTL_SECS as f64;
+    let removed: Option<i64> = log_error(
+        redis
+            .eval(
+                DISCONNECT_SCRIPT,
+                vec![status_key(user_id), connections_key(user_id)],
+                vec![connection_id.to_string(), cutoff.to_string()],
+            )
+            .await,
+    );
+    removed == Some(1)
+}
+
+/// Tell every server the user shares that their status changed. Invisible users are
+/// announced as offline.
+pub async fn announce(redis: &Client, server_ids: &[Uuid], user_id: Uuid, status: UserStatus) {
+    let status = match status {
+        UserStatus::Invisible => UserStatus::Offline,
+        status => status,
+    };
+    let event = ServerEvent::PresenceUpdate { user_id, status };
+    for server_id in server_ids {
+        crate::publish_event(redis, &format!("server:{server_id}"), &event).await;
+    }
+}
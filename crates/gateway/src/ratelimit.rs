@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+/// Per-connection inbound event limits, read from the environment at startup.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    /// Sustained events per second (`GATEWAY_EVENTS_PER_SEC`).
+    pub per_sec: f64,
+    /// Events allowed in a burst on top of the sustained rate (`GATEWAY_EVENT_BURST`).
+    pub burst: f64,
+    /// Dropped events tolerated before the connection is closed (`GATEWAY_MAX_DROPPED`).
+    pub max_dropped: u32,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            per_sec: var("GATEWAY_EVENTS_PER_SEC", 10.0),
+            burst: var("GATEWAY_EVENT_BURST", 20.0),
+            max_dropped: var("GATEWAY_MAX_DROPPED", 50),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Over the limit: ignore this event.
+    Drop,
+    /// Over the limit for too long: close the connection.
+    Close,
+}
+
+/// Token bucket for one connection. The drop count only resets once the bucket has fully
+/// refilled, so a client that keeps hammering at just over the limit is still closed.
+pub struct TokenBucket {
+    config: RateLimitConfig,
+    tokens: f64,
+    dropped: u32,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            tokens: config.burst,
+            dropped: 0,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn check(&mut self) -> Verdict {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.config.per_sec).min(self.config.burst);
+        if self.tokens >= self.config.burst {
+            self.dropped = 0;
+        }
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Verdict::Allow;
+        }
+
+        self.dropped += 1;
+        if self.dropped >= self.config.max_dropped {
+            Verdict::Close
+        } else {
+            Verdict::Drop
+        }
+    }
+}
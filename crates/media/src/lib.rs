@@ -1,6 +1,15 @@
-use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::path::Path;
 use thiserror::Error;
-use uuid::Uuid;
+
+mod local;
+#[cfg(feature = "s3")]
+mod s3;
+
+pub use local::LocalStorage;
+#[cfg(feature = "s3")]
+pub use s3::S3Storage;
 
 #[derive(Debug, Error)]
 pub enum MediaError {
@@ -8,53 +17,214 @@ pub enum MediaError {
     NotFound,
     #[error("file too large")]
     TooLarge,
+    #[error("unsupported file type")]
+    UnsupportedType,
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("storage backend error: {0}")]
+    Backend(String),
     #[error("db error: {0}")]
     Db(#[from] rusteze_db::DbError),
 }
 
-/// Local filesystem storage backend. Swap for S3 in production.
-pub struct LocalStorage {
-    base_path: PathBuf,
+/// File extensions accepted by `store`. Anything else, notably executables and scripts,
+/// is rejected with `MediaError::UnsupportedType`. Files without an extension are stored as `bin`.
+pub const ALLOWED_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "avif", // images
+    "mp4", "webm", "mov", // video
+    "mp3", "ogg", "wav", "flac", "m4a", // audio
+    "pdf", "txt", "md", "log", "csv", "json", // documents
+    "zip", "gz", "tar", "7z", "bin", // archives and raw data
+];
+
+/// Content type to serve a file with, derived from its (allowlisted) extension rather than
+/// trusted from the uploader. Unknown extensions are served as opaque bytes.
+pub fn content_type_for(extension: &str) -> &'static str {
+    match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "m4a" => "audio/mp4",
+        "pdf" => "application/pdf",
+        "txt" | "md" | "log" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "7z" => "application/x-7z-compressed",
+        _ => "application/octet-stream",
+    }
 }
 
-impl LocalStorage {
-    pub fn new(base_path: impl Into<PathBuf>) -> Self {
-        Self {
-            base_path: base_path.into(),
-        }
+/// Where `store` put a file.
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    /// Content-addressed path, `hash[0..2]/hash` of the SHA-256 of the data.
+    pub path: String,
+    /// Lowercased extension of the uploaded filename, `bin` if it had none.
+    pub extension: String,
+}
+
+/// A place to keep uploaded files. Callers hold an `Arc<dyn Storage>` so the backend is
+/// picked at startup.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Largest file `store` accepts, in bytes.
+    fn max_bytes(&self) -> usize;
+
+    /// Store `data` under its content hash. Identical uploads share one object, so the
+    /// existing path is returned without rewriting it.
+    async fn store(&self, data: &[u8], filename: &str) -> Result<StoredFile, MediaError>;
+
+    async fn fetch(&self, path: &str) -> Result<Vec<u8>, MediaError>;
+
+    /// Remove a stored file. Paths are shared between identical uploads, so only call this
+    /// once nothing references the path any more.
+    async fn delete(&self, path: &str) -> Result<(), MediaError>;
+}
+
+/// Check `data` against the size limit and extension allowlist, and work out its
+/// content-addressed path. Shared by every backend's `store`.
+pub(crate) fn prepare(
+    data: &[u8],
+    filename: &str,
+    max_bytes: usize,
+) -> Result<StoredFile, MediaError> {
+    if data.len() > max_bytes {
+        return Err(MediaError::TooLarge);
+    }
+
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_else(|| "bin".into());
+    if !ALLOWED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(MediaError::UnsupportedType);
+    }
+
+    let hash = format!("{:x}", Sha256::digest(data));
+    Ok(StoredFile {
+        path: format!("{}/{hash}", &hash[..2]),
+        extension,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    /// In-memory backend to exercise the trait without touching disk or the network.
+    #[derive(Default)]
+    struct MemoryStorage {
+        files: Mutex<HashMap<String, Vec<u8>>>,
     }
 
-    pub async fn store(&self, data: &[u8], filename: &str) -> Result<String, MediaError> {
-        let id = Uuid::now_v7();
-        let ext = Path::new(filename)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("bin");
-        let path = format!("{id}.{ext}");
-        let full_path = self.base_path.join(&path);
+    #[async_trait]
+    impl Storage for MemoryStorage {
+        fn max_bytes(&self) -> usize {
+            16
+        }
+
+        async fn store(&self, data: &[u8], filename: &str) -> Result<StoredFile, MediaError> {
+            let stored = prepare(data, filename, self.max_bytes())?;
+            self.files
+                .lock()
+                .unwrap()
+                .entry(stored.path.clone())
+                .or_insert_with(|| data.to_vec());
+            Ok(stored)
+        }
 
-        // Ensure parent dir exists
-        if let Some(parent) = full_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+        async fn fetch(&self, path: &str) -> Result<Vec<u8>, MediaError> {
+            self.files
+                .lock()
+                .unwrap()
+                .get(path)
+                .cloned()
+                .ok_or(MediaError::NotFound)
         }
 
-        tokio::fs::write(&full_path, data).await?;
-        tracing::info!("stored file: {path} ({} bytes)", data.len());
-        Ok(path)
+        async fn delete(&self, path: &str) -> Result<(), MediaError> {
+            self.files
+                .lock()
+                .unwrap()
+                .remove(path)
+                .map(|_| ())
+                .ok_or(MediaError::NotFound)
+        }
     }
 
-    pub async fn fetch(&self, path: &str) -> Result<Vec<u8>, MediaError> {
-        let full_path = self.base_path.join(path);
-        tokio::fs::read(&full_path)
-            .await
-            .map_err(|_| MediaError::NotFound)
+    #[tokio::test]
+    async fn storage_round_trip_through_trait_object() {
+        let memory = Arc::new(MemoryStorage::default());
+        let storage: Arc<dyn Storage> = memory.clone();
+
+        let stored = storage.store(b"hello", "greeting.txt").await.unwrap();
+        assert_eq!(stored.extension, "txt");
+        assert_eq!(storage.fetch(&stored.path).await.unwrap(), b"hello");
+
+        let again = storage.store(b"hello", "copy.txt").await.unwrap();
+        assert_eq!(again.path, stored.path);
+        assert_eq!(memory.files.lock().unwrap().len(), 1);
+
+        storage.delete(&stored.path).await.unwrap();
+        assert!(matches!(
+            storage.fetch(&stored.path).await,
+            Err(MediaError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn storage_enforces_limits_through_trait_object() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+
+        assert!(matches!(
+            storage.store(&[0; 17], "big.bin").await,
+            Err(MediaError::TooLarge)
+        ));
+        assert!(matches!(
+            storage.store(b"x", "run.exe").await,
+            Err(MediaError::UnsupportedType)
+        ));
+    }
+
+    #[test]
+    fn rejects_data_over_max_bytes() {
+        assert!(prepare(&[0; 10], "a.png", 10).is_ok());
+        assert!(matches!(
+            prepare(&[0; 11], "a.png", 10),
+            Err(MediaError::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn rejects_extensions_outside_allowlist() {
+        for name in ["setup.exe", "run.sh", "page.html", "logo.svg"] {
+            assert!(
+                matches!(prepare(b"x", name, 10), Err(MediaError::UnsupportedType)),
+                "{name} should be rejected"
+            );
+        }
     }
 
-    pub async fn delete(&self, path: &str) -> Result<(), MediaError> {
-        let full_path = self.base_path.join(path);
-        tokio::fs::remove_file(&full_path).await?;
-        Ok(())
+    #[test]
+    fn normalizes_extension() {
+        assert_eq!(prepare(b"x", "Photo.PNG", 10).unwrap().extension, "png");
+        assert_eq!(prepare(b"x", "README", 10).unwrap().extension, "bin");
     }
 }
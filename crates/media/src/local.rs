@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::{MediaError, Storage, StoredFile};
+
+/// Local filesystem storage backend. Use `S3Storage` (feature `s3`) in production.
+pub struct LocalStorage {
+    base_path: PathBuf,
+    max_bytes: usize,
+}
+
+impl LocalStorage {
+    pub fn new(base_path: impl Into<PathBuf>, max_bytes: usize) -> Self {
+        Self {
+            base_path: base_path.into(),
+            max_bytes,
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    async fn store(&self, data: &[u8], filename: &str) -> Result<StoredFile, MediaError> {
+        let stored = crate::prepare(data, filename, self.max_bytes)?;
+        let full_path = self.base_path.join(&stored.path);
+
+        if tokio::fs::try_exists(&full_path).await? {
+            tracing::debug!("file already stored: {}", stored.path);
+            return Ok(stored);
+        }
+
+        // Ensure parent dir exists
+        if let Some(parent) = full_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write to a unique temp file and rename, so a concurrent upload of the same bytes
+        // never observes a partially written file
+        let tmp_path = full_path.with_extension(format!("{}.tmp", Uuid::now_v7()));
+        tokio::fs::write(&tmp_path, data).await?;
+        if let Err(e) = tokio::fs::rename(&tmp_path, &full_path).await {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(e.into());
+        }
+
+        tracing::info!("stored file: {} ({} bytes)", stored.path, data.len());
+        Ok(stored)
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Vec<u8>, MediaError> {
+        let full_path = self.base_path.join(path);
+        tokio::fs::read(&full_path)
+            .await
+            .map_err(|_| MediaError::NotFound)
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), MediaError> {
+        let full_path = self.base_path.join(path);
+        tokio::fs::remove_file(&full_path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_files(dir: &std::path::Path) -> usize {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .map(|path| if path.is_dir() { count_files(&path) } else { 1 })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn identical_uploads_share_one_file() {
+        let base = std::env::temp_dir().join(format!("rusteze-media-{}", Uuid::now_v7()));
+        let storage = LocalStorage::new(&base, 1024);
+
+        let first = storage.store(b"same bytes", "a.png").await.unwrap();
+        let second = storage.store(b"same bytes", "b.png").await.unwrap();
+
+        assert_eq!(first.path, second.path);
+        assert_eq!(count_files(&base), 1);
+        assert_eq!(storage.fetch(&first.path).await.unwrap(), b"same bytes");
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
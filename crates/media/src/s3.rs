@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use object_store::{ObjectStore, aws::AmazonS3, aws::AmazonS3Builder, path::Path as ObjectPath};
+
+use crate::{MediaError, Storage, StoredFile};
+
+/// S3-compatible object storage backend (AWS, MinIO, R2, ...).
+pub struct S3Storage {
+    store: AmazonS3,
+    max_bytes: usize,
+}
+
+impl S3Storage {
+    /// Connect to `bucket` using the standard `AWS_*` environment variables for region,
+    /// credentials and (for non-AWS providers) `AWS_ENDPOINT`.
+    pub fn from_env(bucket: &str, max_bytes: usize) -> Result<Self, MediaError> {
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(backend)?;
+        Ok(Self { store, max_bytes })
+    }
+}
+
+fn backend(e: object_store::Error) -> MediaError {
+    match e {
+        object_store::Error::NotFound { .. } => MediaError::NotFound,
+        e => MediaError::Backend(e.to_string()),
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    async fn store(&self, data: &[u8], filename: &str) -> Result<StoredFile, MediaError> {
+        let stored = crate::prepare(data, filename, self.max_bytes)?;
+        let path = ObjectPath::from(stored.path.as_str());
+
+        match self.store.head(&path).await {
+            Ok(_) => {
+                tracing::debug!("object already stored: {}", stored.path);
+                return Ok(stored);
+            }
+            Err(object_store::Error::NotFound { .. }) => {}
+            Err(e) => return Err(backend(e)),
+        }
+
+        // Object PUTs are atomic, so no temp-and-rename dance is needed here
+        self.store
+            .put(&path, data.to_vec().into())
+            .await
+            .map_err(backend)?;
+
+        tracing::info!("stored object: {} ({} bytes)", stored.path, data.len());
+        Ok(stored)
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Vec<u8>, MediaError> {
+        let result = self
+            .store
+            .get(&ObjectPath::from(path))
+            .await
+            .map_err(backend)?;
+        let bytes = result.bytes().await.map_err(backend)?;
+        Ok(bytes.to_vec())
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), MediaError> {
+        self.store
+            .delete(&ObjectPath::from(path))
+            .await
+            .map_err(backend)?;
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{Channel, Member, Message, PartialUser, Server};
+use crate::{Channel, Member, Message, PartialUser, Reaction, Server};
 
 /// Events sent from server to client over WebSocket.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +16,18 @@ pub enum ServerEvent {
     Pong {
         ts: u64,
     },
+    /// Sent when the gateway had to drop `missed` events for this connection. The client
+    /// should refetch the channels it shows over REST.
+    Resync {
+        missed: u64,
+    },
+    /// Sent after a reconnect catch-up replayed the maximum number of messages for a channel.
+    /// Newer messages may exist; the client should page them over REST starting `after` the
+    /// last replayed one.
+    CatchUpTruncated {
+        channel_id: Uuid,
+        after: Uuid,
+    },
 
     // Messages
     MessageCreate(Message),
@@ -29,6 +41,10 @@ pub enum ServerEvent {
         channel_id: Uuid,
     },
 
+    // Reactions
+    ReactionAdd(Reaction),
+    ReactionRemove(Reaction),
+
     // Channels
     ChannelCreate(Channel),
     ChannelUpdate {
@@ -67,8 +83,15 @@ pub enum ServerEvent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientEvent {
-    Authenticate { token: String },
+    Authenticate {
+        token: String,
+        /// Id of the newest message the client has seen, to replay anything newer on reconnect.
+        last_event_id: Option<Uuid>,
+    },
     Ping { ts: u64 },
     TypingStart { channel_id: Uuid },
     Subscribe { channel_id: Uuid },
+    /// Stop receiving a channel's events. Ignored if the connection isn't subscribed to it.
+    Unsubscribe { channel_id: Uuid },
+    UpdatePresence { status: crate::UserStatus },
 }
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Message;
+
+/// Version of the federation wire format. Bump on incompatible changes to `FederationEvent`.
+pub const FEDERATION_VERSION: u32 = 2;
+
+/// Events exchanged between rusteze deployments. Kept separate from `ServerEvent`, which is
+/// the client protocol and can change without coordinating with other deployments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum FederationEvent {
+    /// A message posted on the origin into a channel shared with the receiver.
+    /// `channel_id` is the receiver's channel id; `author_id` is the origin's user id.
+    MessageCreate(Message),
+}
+
+/// A `FederationEvent` signed by the deployment that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationEnvelope {
+    pub version: u32,
+    /// Name of the origin deployment; the receiver uses it to look up the verifying key.
+    pub origin: String,
+    /// Unix time (seconds) at which the origin signed the envelope. Receivers reject envelopes
+    /// outside a short window, which bounds how long a captured one could be replayed.
+    pub origin_ts: i64,
+    pub event: FederationEvent,
+    /// Signature over `signing_payload(version, origin, origin_ts, event)`.
+    pub signature: String,
+}
+
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    version: u32,
+    origin: &'a str,
+    origin_ts: i64,
+    event: &'a FederationEvent,
+}
+
+impl FederationEnvelope {
+    /// Bytes covered by the signature: the envelope's JSON without the `signature` field.
+    pub fn signing_payload(
+        version: u32,
+        origin: &str,
+        origin_ts: i64,
+        event: &FederationEvent,
+    ) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(&SignedFields {
+            version,
+            origin,
+            origin_ts,
+            event,
+        })
+    }
+}
@@ -3,9 +3,13 @@ pub mod message;
 pub mod server;
 pub mod user;
 pub mod event;
+pub mod federation;
+pub mod permissions;
 
 pub use channel::*;
 pub use message::*;
 pub use server::*;
 pub use user::*;
 pub use event::*;
+pub use federation::*;
+pub use permissions::*;
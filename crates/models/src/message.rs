@@ -35,9 +35,20 @@ pub struct Embed {
     pub image_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub message_id: Uuid,
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    pub emoji: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageCreate {
     pub content: Option<String>,
     pub replies_to: Option<Uuid>,
     pub nonce: Option<String>,
+    /// Ids of files uploaded via `POST /channels/{id}/attachments`.
+    #[serde(default)]
+    pub attachments: Vec<Uuid>,
 }
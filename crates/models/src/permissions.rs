@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// Server permission bits, combined across a member's roles. Stored as `roles.permissions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Permissions(pub u64);
+
+impl Permissions {
+    pub const NONE: Permissions = Permissions(0);
+    /// Grants every permission. Server owners implicitly have it.
+    pub const ADMINISTRATOR: Permissions = Permissions(1 << 0);
+    pub const MANAGE_CHANNELS: Permissions = Permissions(1 << 1);
+    pub const MANAGE_MESSAGES: Permissions = Permissions(1 << 2);
+    pub const CREATE_INVITE: Permissions = Permissions(1 << 3);
+    pub const MANAGE_ROLES: Permissions = Permissions(1 << 4);
+    pub const VIEW_AUDIT_LOG: Permissions = Permissions(1 << 5);
+    pub const ALL: Permissions = Permissions(u64::MAX);
+
+    /// A member's effective permissions: everything for the server owner, otherwise the
+    /// union of their roles.
+    pub fn for_member(is_owner: bool, roles: Permissions) -> Permissions {
+        if is_owner { Permissions::ALL } else { roles }
+    }
+
+    /// Whether every bit of `other` is granted. `ADMINISTRATOR` grants everything.
+    pub fn contains(self, other: Permissions) -> bool {
+        self.0 & Self::ADMINISTRATOR.0 != 0 || self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for Permissions {
+    type Output = Permissions;
+
+    fn bitor(self, rhs: Permissions) -> Permissions {
+        Permissions(self.0 | rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GATED: [Permissions; 3] = [
+        Permissions::MANAGE_CHANNELS,
+        Permissions::MANAGE_MESSAGES,
+        Permissions::CREATE_INVITE,
+    ];
+
+    #[test]
+    fn owner_has_every_permission() {
+        let owner = Permissions::for_member(true, Permissions::NONE);
+        for required in GATED {
+            assert!(owner.contains(required));
+        }
+    }
+
+    #[test]
+    fn plain_member_has_none() {
+        let member = Permissions::for_member(false, Permissions::NONE);
+        for required in GATED {
+            assert!(!member.contains(required));
+        }
+    }
+
+    #[test]
+    fn member_gets_only_their_roles_permissions() {
+        let roles = Permissions::CREATE_INVITE | Permissions::MANAGE_MESSAGES;
+        let member = Permissions::for_member(false, roles);
+        assert!(member.contains(Permissions::CREATE_INVITE));
+        assert!(member.contains(Permissions::MANAGE_MESSAGES));
+        assert!(member.contains(roles));
+        assert!(!member.contains(Permissions::MANAGE_CHANNELS));
+        assert!(!member.contains(Permissions::CREATE_INVITE | Permissions::MANAGE_CHANNELS));
+    }
+
+    #[test]
+    fn administrator_role_implies_everything() {
+        let admin = Permissions::for_member(false, Permissions::ADMINISTRATOR);
+        for required in GATED {
+            assert!(admin.contains(required));
+        }
+    }
+}
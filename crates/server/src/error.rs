@@ -41,6 +41,29 @@ impl From<rusteze_db::DbError> for ApiError {
     }
 }
 
+impl From<rusteze_media::MediaError> for ApiError {
+    fn from(e: rusteze_media::MediaError) -> Self {
+        match e {
+            rusteze_media::MediaError::NotFound => ApiError {
+                status: StatusCode::NOT_FOUND,
+                message: "file not found".into(),
+            },
+            rusteze_media::MediaError::TooLarge => ApiError {
+                status: StatusCode::PAYLOAD_TOO_LARGE,
+                message: "file too large".into(),
+            },
+            rusteze_media::MediaError::UnsupportedType => ApiError {
+                status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                message: "unsupported file type".into(),
+            },
+            _ => ApiError {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message: "internal error".into(),
+            },
+        }
+    }
+}
+
 impl From<rusteze_auth::AuthError> for ApiError {
     fn from(e: rusteze_auth::AuthError) -> Self {
         match e {
@@ -52,6 +75,10 @@ impl From<rusteze_auth::AuthError> for ApiError {
                 status: StatusCode::NOT_FOUND,
                 message: "account not found".into(),
             },
+            rusteze_auth::AuthError::Db(rusteze_db::DbError::AlreadyExists) => ApiError {
+                status: StatusCode::CONFLICT,
+                message: "username or email already in use".into(),
+            },
             rusteze_auth::AuthError::TokenExpired | rusteze_auth::AuthError::InvalidToken => {
                 ApiError {
                     status: StatusCode::UNAUTHORIZED,
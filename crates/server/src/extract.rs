@@ -27,9 +27,10 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
         let token = header.strip_prefix("Bearer ").unwrap_or(header);
 
         let claims =
-            rusteze_auth::token::validate_token(token, &state.jwt_secret)
+            rusteze_auth::token::validate_token(token, &state.jwt_keys)
                 .map_err(|_| StatusCode::UNAUTHORIZED)?;
 
+        tracing::Span::current().record("user_id", tracing::field::display(claims.sub));
         Ok(AuthUser(claims.sub))
     }
 }
@@ -2,19 +2,30 @@ use std::{env, sync::Arc};
 
 use axum::{
     Router,
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post, put},
 };
 use fred::interfaces::ClientLike;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{
+    cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod routes;
 mod state;
 mod error;
 mod extract;
+mod sanitize;
+mod trace;
 
 use state::AppState;
 
+/// Allowance on top of `MAX_UPLOAD_BYTES` for multipart boundaries, part headers and small
+/// extra fields in an upload request.
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
@@ -26,7 +37,28 @@ async fn main() {
         .init();
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let jwt_secret = env::var("JWT_SECRET").unwrap_or_else(|_| "dev-secret-change-me".into());
+    let jwt_keys = rusteze_auth::token::KeyMaterial::from_env().expect("invalid JWT configuration");
+    let federation_peers =
+        rusteze_auth::federation::load_peer_keys(&env::var("FEDERATION_PEERS").unwrap_or_default())
+            .expect("invalid FEDERATION_PEERS");
+    let unique_usernames = env::var("UNIQUE_USERNAMES").is_ok_and(|v| v == "true" || v == "1");
+    let media_path = env::var("MEDIA_PATH").unwrap_or_else(|_| "./media".into());
+    let max_upload_bytes = env::var("MAX_UPLOAD_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(8 * 1024 * 1024);
+    let storage: Arc<dyn rusteze_media::Storage> = match env::var("STORAGE_BACKEND").as_deref() {
+        #[cfg(feature = "s3")]
+        Ok("s3") => {
+            let bucket = env::var("S3_BUCKET").expect("S3_BUCKET must be set for the s3 backend");
+            Arc::new(
+                rusteze_media::S3Storage::from_env(&bucket, max_upload_bytes)
+                    .expect("invalid S3 configuration"),
+            )
+        }
+        Ok("local") | Err(_) => Arc::new(rusteze_media::LocalStorage::new(media_path, max_upload_bytes)),
+        Ok(other) => panic!("unsupported STORAGE_BACKEND: {other}"),
+    };
     let redis_url = env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
     let bind = env::var("BIND").unwrap_or_else(|_| "0.0.0.0:14702".into());
 
@@ -40,7 +72,10 @@ async fn main() {
     let state = Arc::new(AppState {
         db: pool,
         redis,
-        jwt_secret,
+        jwt_keys,
+        federation_peers,
+        unique_usernames,
+        storage,
     });
 
     let app = Router::new()
@@ -55,14 +90,60 @@ async fn main() {
         // Channels
         .route("/servers/{server_id}/channels", post(routes::channels::create_channel))
         .route("/servers/{server_id}/channels", get(routes::channels::list_channels))
+        .route(
+            "/servers/{server_id}/channels/{channel_id}",
+            delete(routes::channels::delete_channel),
+        )
+        // Roles
+        .route(
+            "/servers/{server_id}/roles",
+            get(routes::roles::list_roles).post(routes::roles::create_role),
+        )
+        .route(
+            "/servers/{server_id}/members/{user_id}/roles/{role_id}",
+            put(routes::roles::assign_role).delete(routes::roles::unassign_role),
+        )
+        // Members
+        .route("/servers/{server_id}/members/search", get(routes::members::search_members))
         // Messages
         .route("/channels/{channel_id}/messages", get(routes::messages::list_messages))
         .route("/channels/{channel_id}/messages", post(routes::messages::send_message))
+        .route(
+            "/channels/{channel_id}/messages/{message_id}",
+            patch(routes::messages::edit_message).delete(routes::messages::delete_message),
+        )
+        .route(
+            "/channels/{channel_id}/messages/{message_id}/reactions",
+            get(routes::reactions::list_reactions),
+        )
+        .route(
+            "/channels/{channel_id}/messages/{message_id}/reactions/{emoji}",
+            put(routes::reactions::add_reaction).delete(routes::reactions::remove_reaction),
+        )
+        // Attachments; the handler checks MAX_UPLOAD_BYTES on the file while reading it, and
+        // the body limit bounds everything else in the request
+        .route(
+            "/channels/{channel_id}/attachments",
+            post(routes::attachments::upload_attachment)
+                .layer(DefaultBodyLimit::max(max_upload_bytes + MULTIPART_OVERHEAD_BYTES)),
+        )
+        .route("/attachments/{attachment_id}", get(routes::attachments::get_attachment))
+        // Voice
+        .route("/channels/{channel_id}/voice", get(routes::voice::list_participants))
+        .route("/channels/{channel_id}/voice/join", post(routes::voice::join))
+        .route("/channels/{channel_id}/voice/leave", post(routes::voice::leave))
+        .route("/channels/{channel_id}/export", get(routes::messages::export_messages))
         // Invites
         .route("/servers/{server_id}/invites", post(routes::invites::create_invite))
         .route("/invites/{code}/join", post(routes::invites::join_invite))
+        // Federation
+        .route("/federation/inbox", post(routes::federation::inbox))
+        // Audit log
+        .route("/servers/{server_id}/audit", get(routes::audit::list_audit_log))
         .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(trace::request_span))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .with_state(state);
 
     let listener = tokio::net::TcpListener::bind(&bind).await.unwrap();
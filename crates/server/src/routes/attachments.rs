@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Multipart, Path, State, multipart::MultipartError},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use rusteze_db::attachments::AttachmentRow;
+use rusteze_media::MediaError;
+use rusteze_models::Attachment;
+use uuid::Uuid;
+
+use super::messages::verify_channel_access;
+use crate::{error::ApiError, extract::AuthUser, state::AppState};
+
+/// Convert a stored attachment into its public shape, with a download url.
+pub(crate) fn to_attachment(row: &AttachmentRow) -> Attachment {
+    Attachment {
+        id: row.id,
+        filename: row.filename.clone(),
+        content_type: row.content_type.clone(),
+        size: row.size as u64,
+        url: format!("/attachments/{}", row.id),
+    }
+}
+
+/// ASCII-only filename for a quoted `Content-Disposition` parameter. Quotes, backslashes and
+/// anything non-printable become `_`.
+fn disposition_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| {
+            if (c.is_ascii_graphic() || c == ' ') && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn multipart_error(e: MultipartError) -> ApiError {
+    ApiError {
+        status: e.status(),
+        message: e.body_text(),
+    }
+}
+
+/// Upload a single file in the `file` multipart field. The returned id can be referenced
+/// from `MessageCreate::attachments` when sending to the same channel.
+pub async fn upload_attachment(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(channel_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<Attachment>, ApiError> {
+    verify_channel_access(&state, user.0, channel_id).await?;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        // Keep only the final path component of whatever name the client sent
+        let filename = field
+            .file_name()
+            .and_then(|name| name.rsplit(['/', '\\']).next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("upload")
+            .to_string();
+        // `store` enforces the limit too; checking while reading bounds memory use
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+            if data.len() + chunk.len() > state.storage.max_bytes() {
+                return Err(MediaError::TooLarge.into());
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        let stored = state.storage.store(&data, &filename).await?;
+        // The client's claimed type is ignored; it decides how browsers render the file
+        let content_type = rusteze_media::content_type_for(&stored.extension);
+        let row = rusteze_db::attachments::create_attachment(
+            &state.db,
+            user.0,
+            channel_id,
+            &filename,
+            content_type,
+            data.len() as i64,
+            &stored.path,
+        )
+        .await?;
+
+        return Ok(Json(to_attachment(&row)));
+    }
+
+    Err(ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message: "missing file field".into(),
+    })
+}
+
+pub async fn get_attachment(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(attachment_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let row = rusteze_db::attachments::find_by_id(&state.db, attachment_id).await?;
+    let channel_id = row.channel_id.ok_or(ApiError {
+        status: StatusCode::NOT_FOUND,
+        message: "not found".into(),
+    })?;
+    verify_channel_access(&state, user.0, channel_id).await?;
+
+    // Re-derive the type from the stored name and always serve as a download, so nothing
+    // uploaded can render as a page on the API origin
+    let extension = std::path::Path::new(&row.filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let content_type = rusteze_media::content_type_for(&extension);
+
+    let data = state.storage.fetch(&row.storage_path).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}\"",
+                    disposition_filename(&row.filename)
+                ),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
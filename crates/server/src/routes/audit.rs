@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Path, Query, State}};
+use rusteze_models::Permissions;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{error::ApiError, extract::AuthUser, state::AppState};
+
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub before: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+pub async fn list_audit_log(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(server_id): Path<Uuid>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<rusteze_db::audit::AuditLogRow>>, ApiError> {
+    super::require_permission(&state, server_id, user.0, Permissions::VIEW_AUDIT_LOG).await?;
+
+    let limit = query.limit.unwrap_or(50).min(100);
+    let entries =
+        rusteze_db::audit::fetch_entries(&state.db, server_id, query.before, limit).await?;
+    Ok(Json(entries))
+}
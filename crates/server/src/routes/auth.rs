@@ -24,16 +24,44 @@ pub struct AuthResponse {
     pub token: String,
 }
 
+/// Usernames are 2-32 characters. Unique usernames are also restricted to lowercase ASCII
+/// letters, digits, `_` and `.` so lookalikes can't impersonate each other.
+fn validate_username(username: &str, unique: bool) -> Result<(), ApiError> {
+    let len = username.chars().count();
+    if !(2..=32).contains(&len) || username.trim() != username {
+        return Err(ApiError {
+            status: axum::http::StatusCode::BAD_REQUEST,
+            message: "username must be 2-32 characters with no surrounding whitespace".into(),
+        });
+    }
+
+    if unique
+        && !username
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+    {
+        return Err(ApiError {
+            status: axum::http::StatusCode::BAD_REQUEST,
+            message: "username may only contain lowercase letters, digits, '_' and '.'".into(),
+        });
+    }
+
+    Ok(())
+}
+
 pub async fn register(
     State(state): State<Arc<AppState>>,
     Json(body): Json<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, ApiError> {
+    validate_username(&body.username, state.unique_usernames)?;
+
     let result = rusteze_auth::session::register(
         &state.db,
         &body.username,
         &body.email,
         &body.password,
-        &state.jwt_secret,
+        state.unique_usernames,
+        &state.jwt_keys,
     )
     .await?;
 
@@ -51,7 +79,7 @@ pub async fn login(
         &state.db,
         &body.email,
         &body.password,
-        &state.jwt_secret,
+        &state.jwt_keys,
     )
     .await?;
 
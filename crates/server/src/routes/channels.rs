@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::{Path, State}};
+use axum::{Json, extract::{Path, State}, http::StatusCode};
 use serde::Deserialize;
+use rusteze_models::Permissions;
 use uuid::Uuid;
 
 use crate::{error::ApiError, extract::AuthUser, state::AppState};
@@ -23,17 +24,21 @@ pub async fn create_channel(
     Path(server_id): Path<Uuid>,
     Json(body): Json<CreateChannelRequest>,
 ) -> Result<Json<rusteze_db::channels::ChannelRow>, ApiError> {
-    // Verify user is a member
-    if !rusteze_db::members::is_member(&state.db, server_id, user.0).await? {
-        return Err(ApiError {
-            status: axum::http::StatusCode::FORBIDDEN,
-            message: "not a member of this server".into(),
-        });
-    }
+    super::require_permission(&state, server_id, user.0, Permissions::MANAGE_CHANNELS).await?;
 
     let channel =
         rusteze_db::channels::create_channel(&state.db, server_id, &body.name, &body.channel_type)
             .await?;
+
+    super::record_audit(
+        &state,
+        server_id,
+        user.0,
+        rusteze_db::audit::AuditAction::ChannelCreate,
+        Some(&channel.id.to_string()),
+    )
+    .await;
+
     Ok(Json(channel))
 }
 
@@ -52,3 +57,53 @@ pub async fn list_channels(
     let channels = rusteze_db::channels::fetch_server_channels(&state.db, server_id).await?;
     Ok(Json(channels))
 }
+
+pub async fn delete_channel(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((server_id, channel_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    super::require_permission(&state, server_id, user.0, Permissions::MANAGE_CHANNELS).await?;
+
+    let storage_paths =
+        rusteze_db::attachments::channel_storage_paths(&state.db, channel_id).await?;
+    rusteze_db::channels::delete_channel(&state.db, channel_id, server_id).await?;
+
+    super::record_audit(
+        &state,
+        server_id,
+        user.0,
+        rusteze_db::audit::AuditAction::ChannelDelete,
+        Some(&channel_id.to_string()),
+    )
+    .await;
+
+    super::voice::clear_channel(&state, channel_id).await;
+    remove_unreferenced_files(&state, &storage_paths).await;
+
+    // Server topics are only subscribed at connect time, so members who joined since (or
+    // subscribed to the channel directly) only hear it on the channel topic. The gateway
+    // forwards whichever copy arrives first and drops its subscription to the channel.
+    let event = rusteze_models::ServerEvent::ChannelDelete { id: channel_id };
+    super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+    super::publish_event(&state, &format!("server:{server_id}"), &event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete stored files left without any attachment row. Paths are content-addressed and may
+/// be shared with other channels, so each is checked first. Best-effort: a file that survives
+/// here is only wasted space.
+async fn remove_unreferenced_files(state: &AppState, storage_paths: &[String]) {
+    for path in storage_paths {
+        match rusteze_db::attachments::path_in_use(&state.db, path).await {
+            Ok(false) => {
+                if let Err(e) = state.storage.delete(path).await {
+                    tracing::warn!("failed to delete stored file {path}: {e}");
+                }
+            }
+            Ok(true) => {}
+            Err(e) => tracing::warn!("failed to check references to {path}: {e}"),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State, http::StatusCode};
+use fred::{
+    interfaces::KeysInterface,
+    types::{Expiration, SetOptions},
+};
+use rusteze_models::{FEDERATION_VERSION, FederationEnvelope, FederationEvent, ServerEvent};
+
+use crate::{error::ApiError, sanitize::sanitize_content, state::AppState};
+
+/// How far `origin_ts` may be from the local clock, in either direction, before an envelope
+/// is rejected as stale.
+const MAX_ENVELOPE_SKEW_SECS: i64 = 300;
+
+/// Redis key marking an envelope as delivered. Kept for the whole acceptance window (both
+/// sides of it), after which the timestamp check rejects a replay on its own.
+fn seen_key(origin: &str, signature: &str) -> String {
+    format!("federation:seen:{origin}:{signature}")
+}
+
+fn forbidden(message: &str) -> ApiError {
+    ApiError {
+        status: StatusCode::FORBIDDEN,
+        message: message.into(),
+    }
+}
+
+/// Receive a signed event from a peer deployment listed in `FEDERATION_PEERS`.
+///
+/// Skeleton: remote messages are fanned out to local subscribers but not persisted, since
+/// remote authors have no local user rows yet. Any channel id is accepted from a trusted peer;
+/// per-channel sharing agreements come with full federation routing.
+///
+/// Each envelope is accepted once: it must be signed within `MAX_ENVELOPE_SKEW_SECS` of now,
+/// and its signature is remembered so a replay inside that window is refused.
+pub async fn inbox(
+    State(state): State<Arc<AppState>>,
+    Json(envelope): Json<FederationEnvelope>,
+) -> Result<StatusCode, ApiError> {
+    if envelope.version != FEDERATION_VERSION {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("unsupported federation version {}", envelope.version),
+        });
+    }
+
+    let key = state
+        .federation_peers
+        .get(&envelope.origin)
+        .ok_or_else(|| forbidden("unknown origin"))?;
+
+    rusteze_auth::federation::verify(&envelope, key).map_err(|_| ApiError {
+        status: StatusCode::UNAUTHORIZED,
+        message: "invalid signature".into(),
+    })?;
+
+    let skew = (chrono::Utc::now().timestamp() - envelope.origin_ts).abs();
+    if skew > MAX_ENVELOPE_SKEW_SECS {
+        return Err(forbidden("stale envelope"));
+    }
+
+    let first_delivery: Option<String> = state
+        .redis
+        .set(
+            seen_key(&envelope.origin, &envelope.signature),
+            envelope.origin_ts,
+            Some(Expiration::EX(2 * MAX_ENVELOPE_SKEW_SECS)),
+            Some(SetOptions::NX),
+            false,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("federation replay check failed: {e}");
+            ApiError {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message: "internal error".into(),
+            }
+        })?;
+    if first_delivery.is_none() {
+        return Err(forbidden("envelope already delivered"));
+    }
+
+    match envelope.event {
+        FederationEvent::MessageCreate(mut message) => {
+            // 404s if the target channel doesn't exist here
+            rusteze_db::members::channel_server_id(&state.db, message.channel_id).await?;
+
+            // A peer may only speak for its own users, never impersonate one of ours
+            match rusteze_db::users::find_by_id(&state.db, message.author_id).await {
+                Ok(_) => return Err(forbidden("author is a local user")),
+                Err(rusteze_db::DbError::NotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            message.content = message.content.as_deref().map(sanitize_content);
+            let channel_id = message.channel_id;
+            tracing::info!(origin = %envelope.origin, %channel_id, "federated message received");
+
+            let event = ServerEvent::MessageCreate(message);
+            super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+        }
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::{Json, extract::{Path, State}};
+use rusteze_models::Permissions;
 use serde::Serialize;
 use uuid::Uuid;
 
@@ -32,16 +33,20 @@ pub async fn create_invite(
     user: AuthUser,
     Path(server_id): Path<Uuid>,
 ) -> Result<Json<InviteResponse>, ApiError> {
-    if !rusteze_db::members::is_member(&state.db, server_id, user.0).await? {
-        return Err(ApiError {
-            status: axum::http::StatusCode::FORBIDDEN,
-            message: "not a member of this server".into(),
-        });
-    }
+    super::require_permission(&state, server_id, user.0, Permissions::CREATE_INVITE).await?;
 
     let code = generate_invite_code();
     let invite = rusteze_db::invites::create_invite(&state.db, server_id, user.0, &code).await?;
 
+    super::record_audit(
+        &state,
+        server_id,
+        user.0,
+        rusteze_db::audit::AuditAction::InviteCreate,
+        Some(&invite.code),
+    )
+    .await;
+
     Ok(Json(InviteResponse {
         code: invite.code,
         server_id: invite.server_id,
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Path, Query, State}};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{error::ApiError, extract::AuthUser, state::AppState};
+use rusteze_models::{PartialUser, UserStatus};
+
+#[derive(Deserialize)]
+pub struct MemberSearchQuery {
+    #[serde(default)]
+    pub q: String,
+    pub after: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct MemberSearchResult {
+    pub user: PartialUser,
+    pub nickname: Option<String>,
+}
+
+pub async fn search_members(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(server_id): Path<Uuid>,
+    Query(query): Query<MemberSearchQuery>,
+) -> Result<Json<Vec<MemberSearchResult>>, ApiError> {
+    if !rusteze_db::members::is_member(&state.db, server_id, user.0).await? {
+        return Err(ApiError {
+            status: axum::http::StatusCode::FORBIDDEN,
+            message: "not a member of this server".into(),
+        });
+    }
+
+    let limit = query.limit.unwrap_or(25).clamp(1, 100);
+    let rows = rusteze_db::members::search_members(
+        &state.db,
+        server_id,
+        query.q.trim(),
+        query.after,
+        limit,
+    )
+    .await?;
+
+    let results = rows
+        .into_iter()
+        .map(|row| MemberSearchResult {
+            user: PartialUser {
+                id: row.user_id,
+                username: row.username,
+                discriminator: row.discriminator,
+                display_name: row.display_name,
+                avatar_url: row.avatar_url,
+                status: UserStatus::default(),
+            },
+            nickname: row.nickname,
+        })
+        .collect();
+
+    Ok(Json(results))
+}
@@ -1,24 +1,53 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::{Path, Query, State}};
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures::TryStreamExt;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{error::ApiError, extract::AuthUser, state::AppState};
-use rusteze_models::MessageCreate;
+use crate::{
+    error::ApiError,
+    extract::AuthUser,
+    sanitize::{MAX_CONTENT_CHARS, sanitize_content},
+    state::AppState,
+};
+use rusteze_models::{MessageCreate, Permissions};
+
+#[derive(Deserialize)]
+pub struct EditMessageRequest {
+    pub content: String,
+}
 
 #[derive(Deserialize)]
 pub struct MessageQuery {
     pub before: Option<Uuid>,
+    pub after: Option<Uuid>,
     pub limit: Option<i64>,
 }
 
+/// Page size used when streaming a channel export.
+const EXPORT_PAGE_SIZE: i64 = 100;
+
+/// Most attachments a single message may reference.
+const MAX_ATTACHMENTS: usize = 10;
+
+/// Longest accepted client nonce, in bytes. Nonces are stored with the message, so they can't
+/// be left unbounded; 64 fits any UUID or snowflake a client would use.
+const MAX_NONCE_LEN: usize = 64;
+
 /// Check that the user is a member of the server that owns this channel.
-async fn verify_channel_access(
+/// Returns the server id.
+pub(crate) async fn verify_channel_access(
     state: &AppState,
     user_id: Uuid,
     channel_id: Uuid,
-) -> Result<(), ApiError> {
+) -> Result<Uuid, ApiError> {
     let server_id = rusteze_db::members::channel_server_id(&state.db, channel_id)
         .await?
         .ok_or(ApiError {
@@ -32,7 +61,25 @@ async fn verify_channel_access(
             message: "not a member of this server".into(),
         });
     }
-    Ok(())
+    Ok(server_id)
+}
+
+/// Sanitize message content, rejecting it if nothing visible remains or it is too long.
+fn clean_content(raw: &str) -> Result<String, ApiError> {
+    let content = sanitize_content(raw);
+    if content.is_empty() {
+        return Err(ApiError {
+            status: axum::http::StatusCode::BAD_REQUEST,
+            message: "message content is empty".into(),
+        });
+    }
+    if content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(ApiError {
+            status: axum::http::StatusCode::BAD_REQUEST,
+            message: format!("message content exceeds {MAX_CONTENT_CHARS} characters"),
+        });
+    }
+    Ok(content)
 }
 
 pub async fn list_messages(
@@ -43,9 +90,22 @@ pub async fn list_messages(
 ) -> Result<Json<Vec<rusteze_db::messages::MessageRow>>, ApiError> {
     verify_channel_access(&state, user.0, channel_id).await?;
 
+    if query.before.is_some() && query.after.is_some() {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "pass either before or after, not both".into(),
+        });
+    }
+
     let limit = query.limit.unwrap_or(50).min(100);
-    let messages =
-        rusteze_db::messages::fetch_messages(&state.db, channel_id, query.before, limit).await?;
+    let messages = rusteze_db::messages::fetch_messages(
+        &state.db,
+        channel_id,
+        query.before,
+        query.after,
+        limit,
+    )
+    .await?;
     Ok(Json(messages))
 }
 
@@ -57,14 +117,82 @@ pub async fn send_message(
 ) -> Result<Json<rusteze_db::messages::MessageRow>, ApiError> {
     verify_channel_access(&state, user.0, channel_id).await?;
 
-    let msg = rusteze_db::messages::create_message(
+    let mut attachment_ids = body.attachments.clone();
+    attachment_ids.sort_unstable();
+    attachment_ids.dedup();
+
+    let content = match body.content.as_deref() {
+        // Attachment-only messages may send blank content
+        Some(raw) if !attachment_ids.is_empty() && sanitize_content(raw).is_empty() => None,
+        raw => raw.map(clean_content).transpose()?,
+    };
+    if content.is_none() && attachment_ids.is_empty() {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "message needs content or attachments".into(),
+        });
+    }
+    if attachment_ids.len() > MAX_ATTACHMENTS {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("at most {MAX_ATTACHMENTS} attachments per message"),
+        });
+    }
+    if body.nonce.as_ref().is_some_and(|nonce| nonce.len() > MAX_NONCE_LEN) {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("nonce exceeds {MAX_NONCE_LEN} bytes"),
+        });
+    }
+
+    let msg = match rusteze_db::messages::create_message(
         &state.db,
         channel_id,
         user.0,
-        body.content.as_deref(),
+        content.as_deref(),
         body.replies_to,
+        body.nonce.as_deref(),
     )
-    .await?;
+    .await
+    {
+        Ok(msg) => msg,
+        // Retried send with a nonce we've already stored: return the original, don't fan out again
+        Err(rusteze_db::DbError::AlreadyExists) => {
+            let nonce = body.nonce.as_deref().unwrap_or_default();
+            let existing =
+                rusteze_db::messages::find_by_nonce(&state.db, channel_id, user.0, nonce).await?;
+            return Ok(Json(existing));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut attachments = Vec::new();
+    if !attachment_ids.is_empty() {
+        // Linked after the nonce lookup so a retried send doesn't trip over its own, already
+        // linked uploads. Linking is all-or-nothing: unknown, foreign or concurrently claimed
+        // ids link nothing, so the message can be removed without losing anyone's uploads.
+        match rusteze_db::attachments::link_attachments(
+            &state.db,
+            &attachment_ids,
+            msg.id,
+            user.0,
+            channel_id,
+        )
+        .await
+        {
+            Ok(linked) => {
+                attachments = linked.iter().map(super::attachments::to_attachment).collect();
+            }
+            Err(rusteze_db::DbError::NotFound) => {
+                rusteze_db::messages::delete_message(&state.db, msg.id, channel_id).await?;
+                return Err(ApiError {
+                    status: StatusCode::BAD_REQUEST,
+                    message: "invalid attachment ids".into(),
+                });
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
 
     // Publish event to Redis for gateway fan-out
     let event = rusteze_models::ServerEvent::MessageCreate(rusteze_models::Message {
@@ -72,7 +200,7 @@ pub async fn send_message(
         channel_id: msg.channel_id,
         author_id: msg.author_id,
         content: msg.content.clone(),
-        attachments: vec![],
+        attachments,
         embeds: vec![],
         mentions: vec![],
         replies_to: msg.replies_to,
@@ -81,14 +209,130 @@ pub async fn send_message(
         created_at: msg.created_at,
     });
 
-    if let Ok(payload) = serde_json::to_string(&event) {
-        let _: Result<(), _> = fred::interfaces::PubsubInterface::publish(
-            &state.redis,
-            format!("channel:{channel_id}"),
-            payload.as_str(),
-        )
-        .await;
+    super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+
+    Ok(Json(msg))
+}
+
+pub async fn edit_message(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<EditMessageRequest>,
+) -> Result<Json<rusteze_db::messages::MessageRow>, ApiError> {
+    verify_channel_access(&state, user.0, channel_id).await?;
+
+    let existing = rusteze_db::messages::find_by_id(&state.db, message_id, channel_id).await?;
+    if existing.author_id != user.0 {
+        return Err(ApiError {
+            status: axum::http::StatusCode::FORBIDDEN,
+            message: "only the author can edit this message".into(),
+        });
     }
 
+    let content = clean_content(&body.content)?;
+    let msg = rusteze_db::messages::update_message(
+        &state.db,
+        message_id,
+        channel_id,
+        user.0,
+        &content,
+    )
+    .await?;
+
+    let event = rusteze_models::ServerEvent::MessageUpdate {
+        id: msg.id,
+        channel_id: msg.channel_id,
+        content: msg.content.clone(),
+    };
+    super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+
     Ok(Json(msg))
 }
+
+/// Delete a message. Allowed for its author and members with `MANAGE_MESSAGES`.
+pub async fn delete_message(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let server_id = verify_channel_access(&state, user.0, channel_id).await?;
+
+    let existing = rusteze_db::messages::find_by_id(&state.db, message_id, channel_id).await?;
+    if existing.author_id != user.0 {
+        super::require_permission(&state, server_id, user.0, Permissions::MANAGE_MESSAGES).await?;
+    }
+
+    rusteze_db::messages::delete_message(&state.db, message_id, channel_id).await?;
+
+    let event = rusteze_models::ServerEvent::MessageDelete {
+        id: message_id,
+        channel_id,
+    };
+    super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stream every message in the channel as newline-delimited JSON, newest first. Requires
+/// `MANAGE_MESSAGES`. Pages through the database so the full history is never held in memory.
+pub async fn export_messages(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let server_id = verify_channel_access(&state, user.0, channel_id).await?;
+
+    super::require_permission(&state, server_id, user.0, Permissions::MANAGE_MESSAGES).await?;
+
+    // State is the `before` cursor for the next page; `None` once the last page was sent.
+    let db = state.db.clone();
+    let pages = futures::stream::try_unfold(Some(None), move |cursor: Option<Option<Uuid>>| {
+        let db = db.clone();
+        async move {
+            let Some(before) = cursor else {
+                return Ok(None);
+            };
+
+            let rows = rusteze_db::messages::fetch_messages(
+                &db,
+                channel_id,
+                before,
+                None,
+                EXPORT_PAGE_SIZE,
+            )
+            .await?;
+            if rows.is_empty() {
+                return Ok(None);
+            }
+
+            let next = if (rows.len() as i64) < EXPORT_PAGE_SIZE {
+                None
+            } else {
+                rows.last().map(|row| Some(row.id))
+            };
+
+            let mut chunk = String::new();
+            for row in &rows {
+                if let Ok(line) = serde_json::to_string(row) {
+                    chunk.push_str(&line);
+                    chunk.push('\n');
+                }
+            }
+            Ok::<_, rusteze_db::DbError>(Some((chunk, next)))
+        }
+    })
+    .inspect_err(move |e| tracing::error!("export of channel {channel_id} failed: {e}"));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"channel-{channel_id}.ndjson\""),
+            ),
+        ],
+        Body::from_stream(pages),
+    )
+        .into_response())
+}
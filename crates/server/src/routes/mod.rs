@@ -1,11 +1,22 @@
+pub mod attachments;
+pub mod audit;
 pub mod auth;
 pub mod channels;
+pub mod federation;
 pub mod invites;
+pub mod members;
 pub mod messages;
+pub mod reactions;
+pub mod roles;
 pub mod servers;
+pub mod voice;
 
-use axum::Json;
+use axum::{Json, http::StatusCode};
+use rusteze_models::{Permissions, ServerEvent};
 use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{error::ApiError, state::AppState};
 
 pub async fn root() -> Json<Value> {
     Json(json!({
@@ -13,3 +24,76 @@ pub async fn root() -> Json<Value> {
         "ws": "ws://100.119.229.90:14703",
     }))
 }
+
+/// Publish an event to a Redis topic (`channel:{id}`, `server:{id}`, `user:{id}`) for gateway
+/// fan-out. Best-effort: the database write already happened, so failures are only logged.
+pub async fn publish_event(state: &AppState, topic: &str, event: &ServerEvent) {
+    let payload = match serde_json::to_string(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::error!("failed to serialize event for {topic}: {e}");
+            return;
+        }
+    };
+
+    let result: Result<(), _> =
+        fred::interfaces::PubsubInterface::publish(&state.redis, topic, payload.as_str()).await;
+    if let Err(e) = result {
+        tracing::warn!("failed to publish to {topic}: {e}");
+    }
+}
+
+/// Append to a server's audit log. Best-effort like `publish_event`: the action it records has
+/// already been committed, so failing the request here would only invite a retry that repeats
+/// it. Failures are logged instead.
+pub async fn record_audit(
+    state: &AppState,
+    server_id: Uuid,
+    actor_id: Uuid,
+    action: rusteze_db::audit::AuditAction,
+    target: Option<&str>,
+) {
+    let result = rusteze_db::audit::record(&state.db, server_id, actor_id, action, target).await;
+    if let Err(e) = result {
+        tracing::error!("failed to record {} in audit log of {server_id}: {e}", action.as_str());
+    }
+}
+
+/// A member's effective permissions in a server: the union of their roles, or everything for
+/// the owner. Fails with 403 if the user isn't a member.
+pub async fn member_permissions(
+    state: &AppState,
+    server_id: Uuid,
+    user_id: Uuid,
+) -> Result<Permissions, ApiError> {
+    let server = rusteze_db::servers::find_by_id(&state.db, server_id).await?;
+    let is_owner = server.owner_id == user_id;
+
+    if !is_owner && !rusteze_db::members::is_member(&state.db, server_id, user_id).await? {
+        return Err(ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: "not a member of this server".into(),
+        });
+    }
+
+    let bits = rusteze_db::roles::member_permissions(&state.db, server_id, user_id).await?;
+    Ok(Permissions::for_member(is_owner, Permissions(bits as u64)))
+}
+
+/// Fail with 403 unless the user holds `required` in the server. Returns their full set so
+/// callers can make further checks without another lookup.
+pub async fn require_permission(
+    state: &AppState,
+    server_id: Uuid,
+    user_id: Uuid,
+    required: Permissions,
+) -> Result<Permissions, ApiError> {
+    let permissions = member_permissions(state, server_id, user_id).await?;
+    if !permissions.contains(required) {
+        return Err(ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: "missing permission".into(),
+        });
+    }
+    Ok(permissions)
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use rusteze_models::{Reaction, ServerEvent};
+use uuid::Uuid;
+
+use super::messages::verify_channel_access;
+use crate::{error::ApiError, extract::AuthUser, state::AppState};
+
+/// Longest accepted emoji key, in bytes. Covers multi-codepoint emoji and `name:id` custom emoji.
+const MAX_EMOJI_LEN: usize = 64;
+
+fn validate_emoji(emoji: &str) -> Result<(), ApiError> {
+    if emoji.is_empty()
+        || emoji.len() > MAX_EMOJI_LEN
+        || emoji.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "invalid emoji".into(),
+        });
+    }
+    Ok(())
+}
+
+pub async fn list_reactions(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Vec<rusteze_db::reactions::ReactionRow>>, ApiError> {
+    verify_channel_access(&state, user.0, channel_id).await?;
+    rusteze_db::messages::find_by_id(&state.db, message_id, channel_id).await?;
+
+    let reactions = rusteze_db::reactions::list_reactions(&state.db, message_id).await?;
+    Ok(Json(reactions))
+}
+
+pub async fn add_reaction(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((channel_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
+) -> Result<Json<rusteze_db::reactions::ReactionRow>, ApiError> {
+    validate_emoji(&emoji)?;
+    verify_channel_access(&state, user.0, channel_id).await?;
+    rusteze_db::messages::find_by_id(&state.db, message_id, channel_id).await?;
+
+    let reaction =
+        rusteze_db::reactions::add_reaction(&state.db, message_id, user.0, &emoji).await?;
+
+    let event = ServerEvent::ReactionAdd(Reaction {
+        message_id,
+        channel_id,
+        user_id: user.0,
+        emoji,
+    });
+    super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+
+    Ok(Json(reaction))
+}
+
+pub async fn remove_reaction(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((channel_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
+) -> Result<StatusCode, ApiError> {
+    verify_channel_access(&state, user.0, channel_id).await?;
+    rusteze_db::messages::find_by_id(&state.db, message_id, channel_id).await?;
+
+    rusteze_db::reactions::remove_reaction(&state.db, message_id, user.0, &emoji).await?;
+
+    let event = ServerEvent::ReactionRemove(Reaction {
+        message_id,
+        channel_id,
+        user_id: user.0,
+        emoji,
+    });
+    super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
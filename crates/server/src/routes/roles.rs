@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use rusteze_db::audit::AuditAction;
+use rusteze_models::Permissions;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{error::ApiError, extract::AuthUser, state::AppState};
+
+/// Longest accepted role name, in characters.
+const MAX_ROLE_NAME_CHARS: usize = 100;
+
+#[derive(Deserialize)]
+pub struct CreateRoleRequest {
+    pub name: String,
+    #[serde(default)]
+    pub permissions: Permissions,
+}
+
+/// Refuse to hand out permissions the actor doesn't hold, so `MANAGE_ROLES` can't be used to
+/// escalate to `ADMINISTRATOR`.
+fn check_grantable(actor: Permissions, granted: Permissions) -> Result<(), ApiError> {
+    if !actor.contains(granted) {
+        return Err(ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: "cannot grant permissions you do not have".into(),
+        });
+    }
+    Ok(())
+}
+
+pub async fn list_roles(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(server_id): Path<Uuid>,
+) -> Result<Json<Vec<rusteze_db::roles::RoleRow>>, ApiError> {
+    super::member_permissions(&state, server_id, user.0).await?;
+
+    let roles = rusteze_db::roles::fetch_server_roles(&state.db, server_id).await?;
+    Ok(Json(roles))
+}
+
+pub async fn create_role(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<CreateRoleRequest>,
+) -> Result<Json<rusteze_db::roles::RoleRow>, ApiError> {
+    let actor =
+        super::require_permission(&state, server_id, user.0, Permissions::MANAGE_ROLES).await?;
+    check_grantable(actor, body.permissions)?;
+
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_ROLE_NAME_CHARS {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("role name must be 1-{MAX_ROLE_NAME_CHARS} characters"),
+        });
+    }
+
+    let role =
+        rusteze_db::roles::create_role(&state.db, server_id, name, body.permissions.0 as i64)
+            .await?;
+
+    super::record_audit(
+        &state,
+        server_id,
+        user.0,
+        AuditAction::RoleUpdate,
+        Some(&role.id.to_string()),
+    )
+    .await;
+
+    Ok(Json(role))
+}
+
+/// Check the actor may change this member's roles, including handing out this role.
+async fn verify_role_change(
+    state: &AppState,
+    actor_id: Uuid,
+    server_id: Uuid,
+    user_id: Uuid,
+    role_id: Uuid,
+) -> Result<(), ApiError> {
+    let actor =
+        super::require_permission(state, server_id, actor_id, Permissions::MANAGE_ROLES).await?;
+
+    let role = rusteze_db::roles::find_by_id(&state.db, role_id, server_id).await?;
+    check_grantable(actor, Permissions(role.permissions as u64))?;
+
+    if !rusteze_db::members::is_member(&state.db, server_id, user_id).await? {
+        return Err(ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: "member not found".into(),
+        });
+    }
+    Ok(())
+}
+
+pub async fn assign_role(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((server_id, user_id, role_id)): Path<(Uuid, Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    verify_role_change(&state, user.0, server_id, user_id, role_id).await?;
+
+    rusteze_db::roles::assign_role(&state.db, server_id, user_id, role_id).await?;
+
+    super::record_audit(
+        &state,
+        server_id,
+        user.0,
+        AuditAction::RoleUpdate,
+        Some(&user_id.to_string()),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn unassign_role(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((server_id, user_id, role_id)): Path<(Uuid, Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    verify_role_change(&state, user.0, server_id, user_id, role_id).await?;
+
+    rusteze_db::roles::unassign_role(&state.db, server_id, user_id, role_id).await?;
+
+    super::record_audit(
+        &state,
+        server_id,
+        user.0,
+        AuditAction::RoleUpdate,
+        Some(&user_id.to_string()),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
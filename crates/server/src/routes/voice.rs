@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use fred::interfaces::{KeysInterface, SetsInterface};
+use rusteze_models::ServerEvent;
+use uuid::Uuid;
+
+use super::messages::verify_channel_access;
+use crate::{error::ApiError, extract::AuthUser, state::AppState};
+
+/// Redis set holding the ids of users connected to a voice channel.
+fn voice_key(channel_id: Uuid) -> String {
+    format!("voice:{channel_id}")
+}
+
+fn redis_error(e: fred::error::Error) -> ApiError {
+    tracing::error!("voice state redis error: {e}");
+    ApiError {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        message: "internal error".into(),
+    }
+}
+
+/// Check membership and that the channel is a voice channel.
+async fn verify_voice_channel(
+    state: &AppState,
+    user_id: Uuid,
+    channel_id: Uuid,
+) -> Result<(), ApiError> {
+    verify_channel_access(state, user_id, channel_id).await?;
+
+    let channel = rusteze_db::channels::find_by_id(&state.db, channel_id).await?;
+    if channel.channel_type != "voice" {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "not a voice channel".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Drop the participant set of a deleted channel. Best-effort: the channel is already gone,
+/// so a failure only leaves a stale key behind.
+pub(crate) async fn clear_channel(state: &AppState, channel_id: Uuid) {
+    let result: Result<i64, _> = state.redis.del(voice_key(channel_id)).await;
+    if let Err(e) = result {
+        tracing::warn!("failed to clear voice state of {channel_id}: {e}");
+    }
+}
+
+pub async fn list_participants(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<Vec<Uuid>>, ApiError> {
+    verify_voice_channel(&state, user.0, channel_id).await?;
+
+    let members: Vec<String> = state
+        .redis
+        .smembers(voice_key(channel_id))
+        .await
+        .map_err(redis_error)?;
+    let participants = members.iter().filter_map(|m| m.parse().ok()).collect();
+    Ok(Json(participants))
+}
+
+/// Join a voice channel. Joining twice is a no-op and doesn't re-announce the user.
+pub async fn join(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    verify_voice_channel(&state, user.0, channel_id).await?;
+
+    let added: i64 = state
+        .redis
+        .sadd(voice_key(channel_id), user.0.to_string())
+        .await
+        .map_err(redis_error)?;
+    if added > 0 {
+        let event = ServerEvent::VoiceJoin {
+            channel_id,
+            user_id: user.0,
+        };
+        super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Leave a voice channel. Leaving one you aren't in is a no-op.
+pub async fn leave(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(channel_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    verify_voice_channel(&state, user.0, channel_id).await?;
+
+    let removed: i64 = state
+        .redis
+        .srem(voice_key(channel_id), user.0.to_string())
+        .await
+        .map_err(redis_error)?;
+    if removed > 0 {
+        let event = ServerEvent::VoiceLeave {
+            channel_id,
+            user_id: user.0,
+        };
+        super::publish_event(&state, &format!("channel:{channel_id}"), &event).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
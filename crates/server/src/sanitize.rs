@@ -0,0 +1,128 @@
+use std::borrow::Cow;
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Longest message content accepted after sanitization, in characters.
+pub const MAX_CONTENT_CHARS: usize = 4000;
+
+/// Longest run of blank lines kept outside code blocks; anything beyond this is spam, not
+/// formatting.
+const MAX_BLANK_LINES: usize = 1;
+
+/// Longest run of spaces and tabs kept outside code blocks.
+const MAX_CONSECUTIVE_SPACES: usize = 4;
+
+/// Normalize user-supplied message content before it is stored.
+///
+/// - NFC-normalizes so visually identical text compares equal
+/// - strips control characters (keeping `\n` and `\t`), invisible zero-width characters,
+///   and bidi overrides that can disguise links
+/// - collapses runs of blank lines and of spaces and tabs, except inside ``` code blocks
+///   where whitespace carries meaning
+/// - trims surrounding whitespace
+///
+/// Markdown syntax is left intact. Returns an empty string if nothing visible remains.
+pub fn sanitize_content(input: &str) -> String {
+    let stripped: String = input.nfc().filter(|c| !is_stripped(*c)).collect();
+    collapse_whitespace(&stripped).trim().to_string()
+}
+
+/// Cap blank lines at `MAX_BLANK_LINES` and runs of horizontal whitespace at
+/// `MAX_CONSECUTIVE_SPACES`, leaving fenced code blocks untouched.
+fn collapse_whitespace(input: &str) -> String {
+    let mut lines: Vec<Cow<'_, str>> = Vec::new();
+    let mut in_fence = false;
+    let mut blank_lines = 0;
+
+    for line in input.split('\n') {
+        let is_fence = line.trim_start().starts_with("```");
+        if in_fence || is_fence {
+            blank_lines = 0;
+            lines.push(Cow::Borrowed(line));
+        } else if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines <= MAX_BLANK_LINES {
+                lines.push(Cow::Borrowed(""));
+            }
+        } else {
+            blank_lines = 0;
+            lines.push(Cow::Owned(collapse_spaces(line)));
+        }
+        if is_fence {
+            in_fence = !in_fence;
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn collapse_spaces(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut run = 0;
+    for c in line.chars() {
+        if c.is_whitespace() {
+            run += 1;
+            if run > MAX_CONSECUTIVE_SPACES {
+                continue;
+            }
+        } else {
+            run = 0;
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_stripped(c: char) -> bool {
+    (c.is_control() && c != '\n' && c != '\t')
+        || matches!(
+            c,
+            '\u{00AD}' // soft hyphen
+                | '\u{180E}' // mongolian vowel separator
+                | '\u{200B}' // zero-width space
+                | '\u{2060}' // word joiner
+                | '\u{FEFF}' // zero-width no-break space / BOM
+                | '\u{202A}'..='\u{202E}' // bidi embeddings and overrides
+                | '\u{2066}'..='\u{2069}' // bidi isolates
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapses_whitespace_outside_fences() {
+        let input = "hello          world\n\n\n\n\nbye";
+        assert_eq!(sanitize_content(input), "hello    world\n\nbye");
+    }
+
+    #[test]
+    fn leaves_code_fences_intact() {
+        let code = "```py\ndef a():\n        pass\n\n\ndef b():\n        pass\n```";
+        let input = format!("look:\n{code}\n\n\n\nafter          it");
+        assert_eq!(
+            sanitize_content(&input),
+            format!("look:\n{code}\n\nafter    it")
+        );
+    }
+
+    #[test]
+    fn strips_zero_width_and_bidi_characters() {
+        let input = "pay\u{200B}pal\u{FEFF}.com \u{202E}gpj.exe\u{202C} \u{2066}ok\u{2069}";
+        assert_eq!(sanitize_content(input), "paypal.com gpj.exe ok");
+    }
+
+    #[test]
+    fn keeps_newlines_and_tabs_but_strips_other_controls() {
+        assert_eq!(sanitize_content("a\u{0007}b\tc\nd"), "ab\tc\nd");
+    }
+
+    #[test]
+    fn control_only_input_is_empty() {
+        assert_eq!(
+            sanitize_content("\u{0000}\u{0007}\u{001B}\u{007F}\r\n\t"),
+            ""
+        );
+    }
+}
@@ -1,7 +1,16 @@
+use std::sync::Arc;
+
+use rusteze_auth::{federation::PeerKeys, token::KeyMaterial};
+use rusteze_media::Storage;
 use sqlx::PgPool;
 
 pub struct AppState {
     pub db: PgPool,
     pub redis: fred::clients::Client,
-    pub jwt_secret: String,
+    pub jwt_keys: KeyMaterial,
+    pub federation_peers: PeerKeys,
+    /// Discriminator-free, globally unique usernames (`UNIQUE_USERNAMES`).
+    pub unique_usernames: bool,
+    /// Attachment storage, capped at `MAX_UPLOAD_BYTES` per file.
+    pub storage: Arc<dyn Storage>,
 }
@@ -0,0 +1,21 @@
+use axum::{body::Body, http::Request};
+use tracing::Span;
+
+/// Root span for every HTTP request. `request_id` comes from the `x-request-id` header
+/// (set by `SetRequestIdLayer` when the client didn't send one); `user_id` is filled in by
+/// the `AuthUser` extractor once the token is validated.
+pub fn request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id,
+        user_id = tracing::field::Empty,
+    )
+}
@@ -95,4 +95,53 @@ else
 fi
 echo
 
+# 10. Non-member's Subscribe is ignored
+echo "--- 10. Non-member cannot subscribe to a channel ---"
+RESP_C=$(curl -s -X POST "$API/auth/register" \
+  -H "Content-Type: application/json" \
+  -d '{"username":"carol","email":"carol@test.com","password":"password789"}')
+TOKEN_C=$(echo "$RESP_C" | python3 -c "import sys,json; print(json.load(sys.stdin)['token'])")
+if command -v websocat &>/dev/null; then
+  WS_OUT=$(mktemp)
+  {
+    echo "{\"type\":\"Authenticate\",\"token\":\"$TOKEN_C\"}"
+    echo "{\"type\":\"Subscribe\",\"channel_id\":\"$CHANNEL_ID\"}"
+    sleep 3
+  } | timeout 5 websocat "$GW" > "$WS_OUT" 2>/dev/null &
+  WS_PID=$!
+  sleep 1
+  curl -s -X POST "$API/channels/$CHANNEL_ID/messages" \
+    -H "Content-Type: application/json" \
+    -H "Authorization: Bearer $TOKEN_A" \
+    -d '{"content":"members only"}' > /dev/null
+  wait "$WS_PID" || true
+  if grep -q "members only" "$WS_OUT"; then
+    echo "FAIL: non-member received a message from channel $CHANNEL_ID"
+    rm -f "$WS_OUT"
+    exit 1
+  fi
+  echo "(non-member received nothing from the channel)"
+  rm -f "$WS_OUT"
+else
+  echo "websocat not installed, skipping WS test"
+fi
+echo
+
+# 11. Plain members lack MANAGE_CHANNELS; the owner implicitly has it
+echo "--- 11. Channel creation: plain member vs owner ---"
+STATUS_B=$(curl -s -o /dev/null -w "%{http_code}" -X POST "$API/servers/$SERVER_ID/channels" \
+  -H "Content-Type: application/json" \
+  -H "Authorization: Bearer $TOKEN_B" \
+  -d '{"name":"from-bob"}')
+STATUS_A=$(curl -s -o /dev/null -w "%{http_code}" -X POST "$API/servers/$SERVER_ID/channels" \
+  -H "Content-Type: application/json" \
+  -H "Authorization: Bearer $TOKEN_A" \
+  -d '{"name":"from-alice"}')
+echo "member: $STATUS_B, owner: $STATUS_A"
+if [ "$STATUS_B" != "403" ] || [ "$STATUS_A" != "200" ]; then
+  echo "FAIL: expected member 403 and owner 200"
+  exit 1
+fi
+echo
+
 echo "=== E2E Test Complete ==="